@@ -0,0 +1,165 @@
+use core::ops::{Deref, DerefMut};
+use alloc::{string::String, vec::Vec};
+
+use crate::{error::{check_capacity, BinverseError, BinverseResult}, serialize::{Deserialize, Serialize, SizeBytes, SizedDeserialize}, streams::{Decoder, Encoder}};
+
+/// A [String] with a maximum length of `N` bytes. Unlike a `SizeBytes<1, String>`
+/// field, the length is checked whenever the string is modified, so a value
+/// that exists can always be serialized.
+///
+/// The length is serialized using the smallest [SizeBytes] that fits `N`.
+/// When deserializing, lengths larger than `N` are rejected before any memory
+/// is allocated.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BoundedString<const N: usize>(String);
+
+impl<const N: usize> BoundedString<N> {
+    /// The SizeBytes used to serialize the length.
+    pub const SIZE_BYTES: SizeBytes = SizeBytes::smallest_for(N as u64);
+
+    /// Creates a new empty string.
+    pub fn new() -> Self { Self(String::new()) }
+
+    /// The maximum length in bytes.
+    pub const fn capacity(&self) -> usize { N }
+
+    /// Appends a string slice. Returns [`BinverseError::CapacityExceeded`]
+    /// and leaves the string unchanged if the result would be too long.
+    pub fn push_str(&mut self, s: &str) -> BinverseResult<()> {
+        check_capacity(N, self.0.len() + s.len())?;
+        self.0.push_str(s);
+        Ok(())
+    }
+
+    /// Appends a character. Returns [`BinverseError::CapacityExceeded`]
+    /// and leaves the string unchanged if the result would be too long.
+    pub fn push(&mut self, c: char) -> BinverseResult<()> {
+        check_capacity(N, self.0.len() + c.len_utf8())?;
+        self.0.push(c);
+        Ok(())
+    }
+
+    /// Removes the last character and returns it.
+    pub fn pop(&mut self) -> Option<char> { self.0.pop() }
+
+    /// Removes all characters.
+    pub fn clear(&mut self) { self.0.clear() }
+
+    /// Returns the string as a string slice.
+    pub fn as_str(&self) -> &str { &self.0 }
+
+    /// Returns the inner [String].
+    pub fn into_inner(self) -> String { self.0 }
+}
+
+impl<const N: usize> TryFrom<String> for BoundedString<N> {
+    type Error = BinverseError;
+
+    fn try_from(s: String) -> BinverseResult<Self> {
+        check_capacity(N, s.len())?;
+        Ok(Self(s))
+    }
+}
+impl<const N: usize> TryFrom<&str> for BoundedString<N> {
+    type Error = BinverseError;
+
+    fn try_from(s: &str) -> BinverseResult<Self> {
+        check_capacity(N, s.len())?;
+        Ok(Self(s.into()))
+    }
+}
+impl<const N: usize> Deref for BoundedString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str { &self.0 }
+}
+
+impl<const N: usize> Serialize for BoundedString<N> {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.serialize_sized(Self::SIZE_BYTES, &self.0)
+    }
+}
+impl<const N: usize> Deserialize for BoundedString<N> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        let size = d.read_size(Self::SIZE_BYTES)?;
+        check_capacity(N, size)?;
+        String::deserialize_sized(d, size).map(Self)
+    }
+}
+
+/// A [Vec] with a maximum length of `N` elements. Unlike a `SizeBytes<1, Vec<T>>`
+/// field, the length is checked whenever elements are added, so a value that
+/// exists can always be serialized.
+///
+/// The length is serialized using the smallest [SizeBytes] that fits `N`.
+/// When deserializing, lengths larger than `N` are rejected before any memory
+/// is allocated.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BoundedVec<T, const N: usize>(Vec<T>);
+
+impl<T, const N: usize> BoundedVec<T, N> {
+    /// The SizeBytes used to serialize the length.
+    pub const SIZE_BYTES: SizeBytes = SizeBytes::smallest_for(N as u64);
+
+    /// Creates a new empty vec.
+    pub fn new() -> Self { Self(Vec::new()) }
+
+    /// The maximum number of elements.
+    pub const fn capacity(&self) -> usize { N }
+
+    /// Appends an element. Returns [`BinverseError::CapacityExceeded`] if
+    /// the vec is already full.
+    pub fn push(&mut self, t: T) -> BinverseResult<()> {
+        check_capacity(N, self.0.len() + 1)?;
+        self.0.push(t);
+        Ok(())
+    }
+
+    /// Removes the last element and returns it.
+    pub fn pop(&mut self) -> Option<T> { self.0.pop() }
+
+    /// Shortens the vec to `len` elements.
+    pub fn truncate(&mut self, len: usize) { self.0.truncate(len) }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) { self.0.clear() }
+
+    /// Returns the elements as a slice.
+    pub fn as_slice(&self) -> &[T] { &self.0 }
+
+    /// Returns the inner [Vec].
+    pub fn into_inner(self) -> Vec<T> { self.0 }
+}
+
+impl<T, const N: usize> Default for BoundedVec<T, N> {
+    fn default() -> Self { Self::new() }
+}
+impl<T, const N: usize> TryFrom<Vec<T>> for BoundedVec<T, N> {
+    type Error = BinverseError;
+
+    fn try_from(v: Vec<T>) -> BinverseResult<Self> {
+        check_capacity(N, v.len())?;
+        Ok(Self(v))
+    }
+}
+impl<T, const N: usize> Deref for BoundedVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] { &self.0 }
+}
+impl<T, const N: usize> DerefMut for BoundedVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] { &mut self.0 }
+}
+
+impl<T: Serialize, const N: usize> Serialize for BoundedVec<T, N> {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.serialize_sized(Self::SIZE_BYTES, &self.0)
+    }
+}
+impl<T: Deserialize, const N: usize> Deserialize for BoundedVec<T, N> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        let size = d.read_size(Self::SIZE_BYTES)?;
+        check_capacity(N, size)?;
+        Vec::deserialize_sized(d, size).map(Self)
+    }
+}
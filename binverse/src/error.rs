@@ -1,13 +1,17 @@
 use crate::serialize::SizeBytes;
-use std::fmt;
+use core::fmt;
+
+use crate::io;
 
 /// An error suggesting something went wrong during data (de)serialization.
 /// It can occur due to invald data, an IO error or an error in the
 /// Serialize or Deserialize implementation.
 #[derive(Debug)]
 pub enum BinverseError {
-    /// An io error originating from the underlying data stream.
-    IO(std::io::Error),
+    /// An io error originating from the underlying data stream. Without the
+    /// `std` feature, this is the error type of binverse's own
+    /// [Read](crate::io::Read)/[Write](crate::io::Write) traits.
+    IO(io::Error),
     /// A VarInt was larger than expected suggesting invalid data.
     VarIntOverflow,
     /// A UTF8 text sequence contained invalid characters.
@@ -20,12 +24,79 @@ pub enum BinverseError {
         /// The number of elements found in the data structure.
         found: usize
     },
+    /// Data with a declared length didn't match the number of bytes that
+    /// were actually read.
+    LengthMismatch {
+        /// The declared length in bytes.
+        expected: u64,
+        /// The number of bytes that were read.
+        found: u64
+    },
+    /// A [Checkpoint](crate::streams::Checkpoint) is ahead of the current
+    /// position, so it was created by a different serializer or deserializer.
+    InvalidCheckpoint {
+        /// The offset of the checkpoint.
+        offset: u64,
+        /// The current position.
+        position: u64
+    },
+    /// A bounded data structure like [BoundedString](crate::bounded::BoundedString)
+    /// would exceed its capacity.
+    CapacityExceeded {
+        /// The capacity of the data structure.
+        capacity: usize,
+        /// The length the data structure would have.
+        found: usize
+    },
+    /// A length-delimited message was longer than the configured maximum.
+    MessageTooLarge {
+        /// The maximum message length in bytes.
+        limit: u64,
+        /// The length of the message in bytes.
+        found: u64
+    },
+    /// A fixed size buffer was too small for the data. See
+    /// [`Serializer::new_slice`](crate::streams::Serializer::new_slice).
+    BufferFull {
+        /// The number of bytes needed up to and including the write that
+        /// failed. This is a lower bound of the complete data size, use
+        /// [`serialized_size`](crate::serialized_size) to get the exact size.
+        needed: u64
+    },
+    /// A decoded integer doesn't fit the target type, for example a `usize`
+    /// larger than `u32::MAX` on a 32-bit platform.
+    IntegerOverflow,
+    /// A zero was read for a `NonZero` integer type.
+    ZeroValue,
+    /// A `char` wasn't a valid Unicode scalar value.
+    InvalidChar(u32),
+    /// Data was valid, but not in its canonical form. Only returned by
+    /// deserializers in strict mode.
+    NonCanonical,
+    /// A field marked as `Removed` has to be written for a revision that
+    /// still contains it, but its type doesn't implement [Default].
+    RemovedFieldWithoutDefault,
+    /// A field requested from a projection generated by
+    /// `#[serializable(projection)]` doesn't exist.
+    UnknownField {
+        /// The position of the unknown field in the requested fields.
+        index: usize
+    },
     /// A generic invalid data error
     InvalidData
 }
 /// A type alias for a Result with a BinverseError as the error type.
 pub type BinverseResult<T> = Result<T, BinverseError>;
 
+/// Returns [`BinverseError::CapacityExceeded`] if `len` elements don't fit
+/// into a fixed `capacity`.
+pub(crate) fn check_capacity(capacity: usize, len: usize) -> BinverseResult<()> {
+    if len > capacity {
+        return Err(BinverseError::CapacityExceeded { capacity, found: len });
+    }
+    Ok(())
+}
+
 impl fmt::Display for BinverseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -33,16 +104,28 @@ impl fmt::Display for BinverseError {
             Self::VarIntOverflow => write!(f, "VarInt overflow occured"),
             Self::InvalidUTF8 => write!(f, "Invalid UTF8 data encountered"),
             Self::SizeExceeded { limit, found } => write!(f, "Data structure size was exceeded, maximum allowed length was {} ({:?}) but found {}", limit.maximum(), limit, found),
+            Self::LengthMismatch { expected, found } => write!(f, "Declared length of {} bytes didn't match the {} bytes read", expected, found),
+            Self::InvalidCheckpoint { offset, position } => write!(f, "Checkpoint at offset {} is ahead of the current position {}", offset, position),
+            Self::CapacityExceeded { capacity, found } => write!(f, "Capacity of {} was exceeded with a length of {}", capacity, found),
+            Self::MessageTooLarge { limit, found } => write!(f, "Message of {} bytes exceeds the maximum length of {} bytes", found, limit),
+            Self::BufferFull { needed } => write!(f, "Buffer was full, at least {} bytes are needed", needed),
+            Self::IntegerOverflow => write!(f, "Integer was too large for the target type"),
+            Self::ZeroValue => write!(f, "Found zero for a non-zero integer"),
+            Self::InvalidChar(c) => write!(f, "Invalid char value {:#x}", c),
+            Self::NonCanonical => write!(f, "Data was not in its canonical form"),
+            Self::RemovedFieldWithoutDefault => write!(f, "A removed field without a default value can't be written for an old revision"),
+            Self::UnknownField { index } => write!(f, "Requested field at index {} doesn't exist", index),
             Self::InvalidData => write!(f, "Data was invalid")
         }
     }
 }
 
-impl From<std::io::Error> for BinverseError {
+impl From<io::Error> for BinverseError {
     /// Wraps an IO error in the IO variant of BinverseError.
-    fn from(e: std::io::Error) -> BinverseError {
+    fn from(e: io::Error) -> BinverseError {
         BinverseError::IO(e)
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for BinverseError { }
\ No newline at end of file
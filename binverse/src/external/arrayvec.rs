@@ -0,0 +1,56 @@
+use ::arrayvec::{ArrayString, ArrayVec};
+
+use crate::{error::{check_capacity, BinverseError, BinverseResult}, serialize::{Deserialize, Serialize, SizeBytes, SizedDeserialize, SizedSerialize}, streams::{Decoder, Encoder}};
+
+impl<T: Serialize, const CAP: usize> SizedSerialize for ArrayVec<T, CAP> {
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+        self.as_slice().serialize_sized(s, size)
+    }
+    fn size(&self) -> usize { self.len() }
+}
+impl<T: Deserialize, const CAP: usize> SizedDeserialize for ArrayVec<T, CAP> {
+    fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self> {
+        check_capacity(CAP, size)?;
+        let mut v = ArrayVec::new();
+        for _ in 0..size {
+            v.push(d.deserialize()?);
+        }
+        Ok(v)
+    }
+}
+impl<T: Serialize, const CAP: usize> Serialize for ArrayVec<T, CAP> {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.serialize_sized(SizeBytes::Var, self)
+    }
+}
+impl<T: Deserialize, const CAP: usize> Deserialize for ArrayVec<T, CAP> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        d.deserialize_sized(SizeBytes::Var)
+    }
+}
+
+impl<const CAP: usize> SizedSerialize for ArrayString<CAP> {
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+        self.as_str().serialize_sized(s, size)
+    }
+    fn size(&self) -> usize { self.len() }
+}
+impl<const CAP: usize> SizedDeserialize for ArrayString<CAP> {
+    fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self> {
+        check_capacity(CAP, size)?;
+        let mut buf = [0; CAP];
+        d.read(&mut buf[..size])?;
+        let s = core::str::from_utf8(&buf[..size]).map_err(|_| BinverseError::InvalidUTF8)?;
+        ArrayString::from(s).map_err(|_| BinverseError::CapacityExceeded { capacity: CAP, found: size })
+    }
+}
+impl<const CAP: usize> Serialize for ArrayString<CAP> {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.serialize_sized(SizeBytes::Var, self)
+    }
+}
+impl<const CAP: usize> Deserialize for ArrayString<CAP> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        d.deserialize_sized(SizeBytes::Var)
+    }
+}
@@ -0,0 +1,37 @@
+use alloc::vec::Vec;
+
+use ::bytes::{Bytes, BytesMut};
+
+use crate::{error::{BinverseError, BinverseResult}, primitives::read_bytes, serialize::{Deserialize, Serialize, SizeBytes, SizedDeserialize, SizedSerialize}, streams::{Decoder, Encoder}};
+
+macro_rules! bytes_impls {
+    ($($t: ty, $from_vec: expr),*) => {
+        $(
+            impl SizedSerialize for $t {
+                fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+                    s.write(self.get(..size).ok_or(BinverseError::InvalidData)?)
+                }
+                fn size(&self) -> usize { self.len() }
+            }
+            impl SizedDeserialize for $t {
+                fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self> {
+                    read_bytes(d, size).map($from_vec)
+                }
+            }
+            impl Serialize for $t {
+                fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+                    s.serialize_sized(SizeBytes::Var, self)
+                }
+            }
+            impl Deserialize for $t {
+                fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+                    d.deserialize_sized(SizeBytes::Var)
+                }
+            }
+        )*
+    };
+}
+bytes_impls!(
+    Bytes, Bytes::from,
+    BytesMut, |b: Vec<u8>| BytesMut::from(b.as_slice())
+);
@@ -0,0 +1,100 @@
+//! Dates are written as the `i32` number of days since January 1, 1 CE and
+//! times as the `u32` seconds since midnight followed by the `u32`
+//! nanoseconds. Date times with a time zone are written in UTC followed by
+//! the offset in seconds for [FixedOffset].
+
+use ::chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+
+use crate::{error::{BinverseError, BinverseResult}, serialize::{Deserialize, FixedSize, Serialize}, streams::{Decoder, Encoder}};
+
+impl Serialize for NaiveDate {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        self.num_days_from_ce().serialize(s)
+    }
+}
+impl Deserialize for NaiveDate {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        NaiveDate::from_num_days_from_ce_opt(d.deserialize()?).ok_or(BinverseError::InvalidData)
+    }
+}
+impl FixedSize for NaiveDate {
+    const SIZE: usize = 4;
+}
+
+impl Serialize for NaiveTime {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        self.num_seconds_from_midnight().serialize(s)?;
+        // larger than a second for leap seconds
+        self.nanosecond().serialize(s)
+    }
+}
+impl Deserialize for NaiveTime {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        NaiveTime::from_num_seconds_from_midnight_opt(d.deserialize()?, d.deserialize()?).ok_or(BinverseError::InvalidData)
+    }
+}
+impl FixedSize for NaiveTime {
+    const SIZE: usize = 8;
+}
+
+impl Serialize for NaiveDateTime {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        self.date().serialize(s)?;
+        self.time().serialize(s)
+    }
+}
+impl Deserialize for NaiveDateTime {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        Ok(NaiveDateTime::new(d.deserialize()?, d.deserialize()?))
+    }
+}
+impl FixedSize for NaiveDateTime {
+    const SIZE: usize = 12;
+}
+
+impl Serialize for FixedOffset {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        self.local_minus_utc().serialize(s)
+    }
+}
+impl Deserialize for FixedOffset {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        FixedOffset::east_opt(d.deserialize()?).ok_or(BinverseError::InvalidData)
+    }
+}
+impl FixedSize for FixedOffset {
+    const SIZE: usize = 4;
+}
+
+impl Serialize for DateTime<Utc> {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        self.naive_utc().serialize(s)
+    }
+}
+impl Deserialize for DateTime<Utc> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        Ok(DateTime::from_naive_utc_and_offset(d.deserialize()?, Utc))
+    }
+}
+impl FixedSize for DateTime<Utc> {
+    const SIZE: usize = 12;
+}
+
+impl Serialize for DateTime<FixedOffset> {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        self.naive_utc().serialize(s)?;
+        self.offset().serialize(s)
+    }
+}
+impl Deserialize for DateTime<FixedOffset> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        let utc: NaiveDateTime = d.deserialize()?;
+        let offset: FixedOffset = d.deserialize()?;
+        // the local time has to be representable as well
+        utc.checked_add_offset(offset).ok_or(BinverseError::InvalidData)?;
+        Ok(DateTime::from_naive_utc_and_offset(utc, offset))
+    }
+}
+impl FixedSize for DateTime<FixedOffset> {
+    const SIZE: usize = 16;
+}
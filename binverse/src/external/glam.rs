@@ -0,0 +1,35 @@
+use ::glam::{DMat2, DMat3, DMat4, DQuat, DVec2, DVec3, DVec4, IVec2, IVec3, IVec4, Mat2, Mat3, Mat4, Quat, UVec2, UVec3, UVec4, Vec2, Vec3, Vec3A, Vec4};
+
+use crate::{error::BinverseResult, serialize::{Deserialize, FixedSize, Serialize}, streams::{Decoder, Encoder}};
+
+macro_rules! glam_impls {
+    ($to_array: ident, $from_array: ident; $($t: ty, $component: ty, $n: expr),*) => {
+        $(
+            impl Serialize for $t {
+                fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+                    self.$to_array().serialize(s)
+                }
+            }
+            impl Deserialize for $t {
+                fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+                    d.deserialize::<[$component; $n]>().map(|a| <$t>::$from_array(&a))
+                }
+            }
+            impl FixedSize for $t {
+                const SIZE: usize = <[$component; $n]>::SIZE;
+            }
+        )*
+    };
+}
+glam_impls!(to_array, from_slice;
+    Vec2, f32, 2, Vec3, f32, 3, Vec3A, f32, 3, Vec4, f32, 4,
+    DVec2, f64, 2, DVec3, f64, 3, DVec4, f64, 4,
+    IVec2, i32, 2, IVec3, i32, 3, IVec4, i32, 4,
+    UVec2, u32, 2, UVec3, u32, 3, UVec4, u32, 4,
+    Quat, f32, 4, DQuat, f64, 4
+);
+// matrices in column-major order
+glam_impls!(to_cols_array, from_cols_slice;
+    Mat2, f32, 4, Mat3, f32, 9, Mat4, f32, 16,
+    DMat2, f64, 4, DMat3, f64, 9, DMat4, f64, 16
+);
@@ -0,0 +1,73 @@
+//! Maps and sets are written in iteration order, which is also used in
+//! canonical mode because the order is part of the value. Duplicate keys are
+//! rejected when reading.
+
+use core::hash::{BuildHasher, Hash};
+
+use ::indexmap::{IndexMap, IndexSet};
+
+use crate::{error::{BinverseError, BinverseResult}, serialize::{Deserialize, Serialize, SizeBytes, SizedDeserialize, SizedSerialize}, streams::{Decoder, Encoder}};
+
+impl<K: Serialize, V: Serialize, S> SizedSerialize for IndexMap<K, V, S> {
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+        for (k, v) in self.iter().take(size) {
+            k.serialize(s)?;
+            v.serialize(s)?;
+        }
+        Ok(())
+    }
+    fn size(&self) -> usize { self.len() }
+}
+impl<K: Deserialize + Eq + Hash, V: Deserialize, S: BuildHasher + Default> SizedDeserialize for IndexMap<K, V, S> {
+    fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self> {
+        let mut map = IndexMap::with_hasher(S::default());
+        for _ in 0..size {
+            let k = d.deserialize()?;
+            if map.insert(k, d.deserialize()?).is_some() {
+                return Err(BinverseError::InvalidData);
+            }
+        }
+        Ok(map)
+    }
+}
+impl<K: Serialize, V: Serialize, S> Serialize for IndexMap<K, V, S> {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.serialize_sized(SizeBytes::Var, self)
+    }
+}
+impl<K: Deserialize + Eq + Hash, V: Deserialize, S: BuildHasher + Default> Deserialize for IndexMap<K, V, S> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        d.deserialize_sized(SizeBytes::Var)
+    }
+}
+
+impl<T: Serialize, S> SizedSerialize for IndexSet<T, S> {
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+        for t in self.iter().take(size) {
+            t.serialize(s)?;
+        }
+        Ok(())
+    }
+    fn size(&self) -> usize { self.len() }
+}
+impl<T: Deserialize + Eq + Hash, S: BuildHasher + Default> SizedDeserialize for IndexSet<T, S> {
+    fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self> {
+        let mut set = IndexSet::with_hasher(S::default());
+        for _ in 0..size {
+            if !set.insert(d.deserialize()?) {
+                return Err(BinverseError::InvalidData);
+            }
+        }
+        Ok(set)
+    }
+}
+impl<T: Serialize, S> Serialize for IndexSet<T, S> {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.serialize_sized(SizeBytes::Var, self)
+    }
+}
+impl<T: Deserialize + Eq + Hash, S: BuildHasher + Default> Deserialize for IndexSet<T, S> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        d.deserialize_sized(SizeBytes::Var)
+    }
+}
@@ -0,0 +1,30 @@
+//! Serialize/Deserialize implementations for third-party types, each enabled
+//! by a feature with the name of the crate:
+//!
+//! - `uuid`: `Uuid` as its 16 bytes.
+//! - `chrono`: `NaiveDate`, `NaiveTime`, `NaiveDateTime`, `FixedOffset` and
+//!   `DateTime` with the `Utc` or `FixedOffset` time zone.
+//! - `bytes`: `Bytes` and `BytesMut` like a `Vec<u8>`.
+//! - `smallvec`: `SmallVec` like a `Vec`.
+//! - `arrayvec`: `ArrayVec` and `ArrayString` like a `Vec`/`String`. Lengths
+//!   larger than the capacity are rejected before reading any elements.
+//! - `indexmap`: `IndexMap` and `IndexSet` in iteration order.
+//! - `glam`: vectors, quaternions and matrices as arrays of their components.
+//!
+//! The collections implement [SizedSerialize](crate::serialize::SizedSerialize)/[SizedDeserialize](crate::serialize::SizedDeserialize),
+//! so they can be used with `SizeBytes` in `#[serializable]` structs.
+
+#[cfg(feature = "uuid")]
+mod uuid;
+#[cfg(feature = "chrono")]
+mod chrono;
+#[cfg(feature = "bytes")]
+mod bytes;
+#[cfg(feature = "smallvec")]
+mod smallvec;
+#[cfg(feature = "arrayvec")]
+mod arrayvec;
+#[cfg(feature = "indexmap")]
+mod indexmap;
+#[cfg(feature = "glam")]
+mod glam;
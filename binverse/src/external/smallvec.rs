@@ -0,0 +1,29 @@
+use ::smallvec::{Array, SmallVec};
+
+use crate::{error::BinverseResult, serialize::{Deserialize, Serialize, SizeBytes, SizedDeserialize, SizedSerialize}, streams::{Decoder, Encoder}};
+
+impl<A: Array> SizedSerialize for SmallVec<A>
+where A::Item: Serialize {
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+        self.as_slice().serialize_sized(s, size)
+    }
+    fn size(&self) -> usize { self.len() }
+}
+impl<A: Array> SizedDeserialize for SmallVec<A>
+where A::Item: Deserialize {
+    fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self> {
+        (0..size).map(|_| d.deserialize()).collect()
+    }
+}
+impl<A: Array> Serialize for SmallVec<A>
+where A::Item: Serialize {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.serialize_sized(SizeBytes::Var, self)
+    }
+}
+impl<A: Array> Deserialize for SmallVec<A>
+where A::Item: Deserialize {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        d.deserialize_sized(SizeBytes::Var)
+    }
+}
@@ -0,0 +1,17 @@
+use ::uuid::Uuid;
+
+use crate::{error::BinverseResult, serialize::{Deserialize, FixedSize, Serialize}, streams::{Decoder, Encoder}};
+
+impl Serialize for Uuid {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.write(self.as_bytes())
+    }
+}
+impl Deserialize for Uuid {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        d.deserialize().map(Uuid::from_bytes)
+    }
+}
+impl FixedSize for Uuid {
+    const SIZE: usize = 16;
+}
@@ -0,0 +1,190 @@
+//! With the `std` feature enabled, this module simply re-exports the
+//! `std::io` traits, so any [`std::io::Read`]/[`std::io::Write`] can be used
+//! with [Serializer](crate::streams::Serializer) and
+//! [Deserializer](crate::streams::Deserializer).
+//!
+//! Without `std`, minimal replacements are defined that only provide the
+//! functionality needed by binverse. They are implemented for byte slices,
+//! mutable byte slices (fixed size buffers) and, with the `alloc` feature,
+//! for [`Vec<u8>`](alloc::vec::Vec).
+//!
+//! [SliceWriter] is available in both cases and writes into a fixed size
+//! buffer while keeping track of the used length.
+
+#[cfg(feature = "std")]
+pub use std::io::{sink, Error, ErrorKind, Read, Sink, Write};
+
+#[cfg(not(feature = "std"))]
+pub use no_std::*;
+
+/// A writer into a fixed size buffer, used by
+/// [`Serializer::new_slice`](crate::streams::Serializer::new_slice).
+/// Unlike writing to a `&mut [u8]` directly, the number of bytes written can
+/// be retrieved afterwards.
+pub struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize
+}
+impl<'a> SliceWriter<'a> {
+    /// Creates a new writer into the buffer.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// The number of bytes written.
+    pub fn len(&self) -> usize { self.len }
+
+    /// Returns true if nothing was written yet.
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// The total size of the buffer.
+    pub fn capacity(&self) -> usize { self.buf.len() }
+
+    /// The part of the buffer that was written.
+    pub fn written(&self) -> &[u8] { &self.buf[..self.len] }
+
+    /// Returns the part of the buffer that was written.
+    pub fn into_written(self) -> &'a mut [u8] { &mut self.buf[..self.len] }
+
+    /// Copies as many bytes as possible and returns the number of bytes copied.
+    fn copy(&mut self, buf: &[u8]) -> usize {
+        let n = buf.len().min(self.buf.len() - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&buf[..n]);
+        self.len += n;
+        n
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> Write for SliceWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Ok(self.copy(buf))
+    }
+    fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+#[cfg(not(feature = "std"))]
+impl<'a> Write for SliceWriter<'a> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+        if buf.len() > self.buf.len() - self.len {
+            return Err(ErrorKind::WriteZero.into());
+        }
+        self.copy(buf);
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+    use core::fmt;
+
+    /// The kind of an io [Error].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum ErrorKind {
+        /// The reader ended before the requested number of bytes were read.
+        UnexpectedEof,
+        /// The writer couldn't accept all bytes, for example because a
+        /// fixed size buffer was full.
+        WriteZero,
+        /// Any other error of a custom reader or writer.
+        Other
+    }
+
+    /// An error of a [Read] or [Write] implementation.
+    #[derive(Debug)]
+    pub struct Error {
+        kind: ErrorKind
+    }
+    impl Error {
+        /// Returns the kind of the error.
+        pub fn kind(&self) -> ErrorKind { self.kind }
+    }
+    impl From<ErrorKind> for Error {
+        fn from(kind: ErrorKind) -> Self {
+            Self { kind }
+        }
+    }
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.kind {
+                ErrorKind::UnexpectedEof => write!(f, "unexpected end of file"),
+                ErrorKind::WriteZero => write!(f, "failed to write whole buffer"),
+                ErrorKind::Other => write!(f, "other error")
+            }
+        }
+    }
+
+    /// A source of bytes, the `no_std` replacement for `std::io::Read`.
+    pub trait Read {
+        /// Reads exactly enough bytes to fill `buf` or returns an error.
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error>;
+    }
+
+    /// A destination for bytes, the `no_std` replacement for `std::io::Write`.
+    pub trait Write {
+        /// Writes all bytes of `buf` or returns an error.
+        fn write_all(&mut self, buf: &[u8]) -> Result<(), Error>;
+
+        /// Writes data that was buffered by the writer. Does nothing by default.
+        fn flush(&mut self) -> Result<(), Error> { Ok(()) }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+            (**self).read_exact(buf)
+        }
+    }
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+            (**self).write_all(buf)
+        }
+        fn flush(&mut self) -> Result<(), Error> {
+            (**self).flush()
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+            if buf.len() > self.len() {
+                *self = &self[self.len()..];
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+            let (a, b) = self.split_at(buf.len());
+            buf.copy_from_slice(a);
+            *self = b;
+            Ok(())
+        }
+    }
+
+    impl Write for &mut [u8] {
+        fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+            if buf.len() > self.len() {
+                return Err(ErrorKind::WriteZero.into());
+            }
+            let (a, b) = core::mem::take(self).split_at_mut(buf.len());
+            a.copy_from_slice(buf);
+            *self = b;
+            Ok(())
+        }
+    }
+
+    /// A writer that discards all bytes, the `no_std` replacement for `std::io::Sink`.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct Sink;
+
+    /// Creates a writer that discards all bytes.
+    pub fn sink() -> Sink { Sink }
+
+    impl Write for Sink {
+        fn write_all(&mut self, _buf: &[u8]) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl Write for alloc::vec::Vec<u8> {
+        fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+            self.extend_from_slice(buf);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use core::marker::PhantomData;
+
+use crate::{error::{BinverseError, BinverseResult}, serialize::{Deserialize, FixedSize}, streams::Deserializer, varint::VarInt};
+#[cfg(feature = "alloc")]
+use crate::{serialize::Serialize, streams::{sub_serializer, Encoder}};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Serializes a slice together with an offset table so single elements can be
+/// read using [`LazyVec::indexed`] without deserializing the elements before
+/// them. This is useful for elements with a variable size, slices of
+/// [FixedSize] elements can be serialized as usual and read using
+/// [`LazyVec::fixed`].
+///
+/// The data consists of the number of elements as a varint, followed by
+/// `len + 1` [u64] offsets and the serialized elements. The offsets are
+/// relative to the start of the element data, the last offset is the total
+/// length of the element data.
+#[cfg(feature = "alloc")]
+pub fn serialize_indexed<T: Serialize, E: Encoder>(s: &mut E, elements: &[T]) -> BinverseResult<()> {
+    let mut data = Vec::new();
+    let mut offsets = Vec::with_capacity(elements.len() + 1);
+    let mut data_s = sub_serializer(s, &mut data);
+    for element in elements {
+        offsets.push(data_s.bytes_written());
+        element.serialize(&mut data_s)?;
+    }
+    offsets.push(data_s.bytes_written());
+
+    VarInt(elements.len() as u64).serialize(s)?;
+    for offset in offsets {
+        offset.serialize(s)?;
+    }
+    s.write(&data)
+}
+
+enum Layout<'a> {
+    Fixed(usize),
+    Indexed { table: &'a [u8] }
+}
+
+/// A view into a serialized collection that deserializes elements on demand.
+/// Because it only borrows the data, it can be used with memory-mapped files
+/// to avoid reading a large collection completely.
+///
+/// A `LazyVec` can be created in two ways:
+/// - [`LazyVec::fixed`] reads a collection serialized like a regular [Vec]
+///   with [`SizeBytes::Var`](crate::serialize::SizeBytes::Var). The element
+///   type has to be [FixedSize] so the offsets can be computed.
+/// - [`LazyVec::indexed`] reads a collection written using
+///   [serialize_indexed] and supports elements of any size.
+pub struct LazyVec<'a, T> {
+    layout: Layout<'a>,
+    elements: &'a [u8],
+    len: usize,
+    encoded_len: usize,
+    revision: u32,
+    _marker: PhantomData<fn() -> T>
+}
+
+impl<'a, T> LazyVec<'a, T> {
+    /// The number of elements in the collection.
+    pub fn len(&self) -> usize { self.len }
+
+    /// Returns true if the collection doesn't contain any elements.
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// The revision used to deserialize the elements.
+    pub fn revision(&self) -> u32 { self.revision }
+
+    /// Returns the number of bytes of the serialized collection. Data
+    /// following the collection starts at this offset.
+    pub fn encoded_len(&self) -> usize { self.encoded_len }
+}
+
+fn read_len(data: &[u8], revision: u32) -> BinverseResult<(usize, &[u8])> {
+    let mut d = Deserializer::new_no_revision(data, revision);
+    let len = d.deserialize::<VarInt>()?.0;
+    let len = usize::try_from(len).map_err(|_| BinverseError::InvalidData)?;
+    Ok((len, d.finish()))
+}
+
+impl<'a, T: FixedSize> LazyVec<'a, T> {
+    /// Creates a view of a collection serialized as a [Vec] with
+    /// [`SizeBytes::Var`](crate::serialize::SizeBytes::Var) at the start of
+    /// `data`. The elements are deserialized using `revision`.
+    pub fn fixed(data: &'a [u8], revision: u32) -> BinverseResult<Self> {
+        let (len, rest) = read_len(data, revision)?;
+        let size = len.checked_mul(T::SIZE).ok_or(BinverseError::InvalidData)?;
+        if size > rest.len() {
+            return Err(BinverseError::InvalidData);
+        }
+        Ok(Self {
+            layout: Layout::Fixed(T::SIZE),
+            elements: &rest[..size],
+            len,
+            encoded_len: data.len() - rest.len() + size,
+            revision,
+            _marker: PhantomData
+        })
+    }
+}
+
+impl<'a, T> LazyVec<'a, T> {
+    /// Creates a view of a collection written using [serialize_indexed] at
+    /// the start of `data`. The elements are deserialized using `revision`.
+    pub fn indexed(data: &'a [u8], revision: u32) -> BinverseResult<Self> {
+        let (len, rest) = read_len(data, revision)?;
+        let table_len = len.checked_add(1).and_then(|n| n.checked_mul(8)).ok_or(BinverseError::InvalidData)?;
+        if table_len > rest.len() {
+            return Err(BinverseError::InvalidData);
+        }
+        let (table, elements) = rest.split_at(table_len);
+        let mut view = Self {
+            layout: Layout::Indexed { table },
+            elements,
+            len,
+            encoded_len: 0,
+            revision,
+            _marker: PhantomData
+        };
+        let (_, data_len) = view.bounds(len)?;
+        view.elements = &elements[..data_len];
+        view.encoded_len = data.len() - elements.len() + data_len;
+        Ok(view)
+    }
+
+    /// Returns the byte range of element `i` for indexed collections. For
+    /// `i == len`, both values are the end of the element data.
+    fn bounds(&self, i: usize) -> BinverseResult<(usize, usize)> {
+        let offset = |i: usize| -> BinverseResult<usize> {
+            let table = match &self.layout {
+                Layout::Indexed { table } => table,
+                Layout::Fixed(_) => unreachable!()
+            };
+            let bytes = table[8 * i..8 * i + 8].try_into().unwrap();
+            usize::try_from(u64::from_le_bytes(bytes)).map_err(|_| BinverseError::InvalidData)
+        };
+        let start = offset(i)?;
+        let end = if i == self.len { start } else { offset(i + 1)? };
+        if start > end || end > self.elements.len() {
+            return Err(BinverseError::InvalidData);
+        }
+        Ok((start, end))
+    }
+}
+
+impl<'a, T: Deserialize> LazyVec<'a, T> {
+    /// Deserializes the element at index `i`. Returns `None` if the index is
+    /// out of bounds.
+    pub fn get(&self, i: usize) -> BinverseResult<Option<T>> {
+        if i >= self.len {
+            return Ok(None);
+        }
+        let bytes = match &self.layout {
+            Layout::Fixed(size) => &self.elements[i * size..(i + 1) * size],
+            Layout::Indexed { .. } => {
+                let (start, end) = self.bounds(i)?;
+                &self.elements[start..end]
+            }
+        };
+        let mut d = Deserializer::new_no_revision(bytes, self.revision);
+        let cp = d.checkpoint();
+        let t = d.deserialize()?;
+        d.verify_len(cp, bytes.len() as u64)?;
+        Ok(Some(t))
+    }
+
+    /// Iterates over all elements, deserializing them one by one.
+    pub fn iter(&self) -> impl Iterator<Item = BinverseResult<T>> + '_ {
+        (0..self.len).map(|i| self.get(i).map(Option::unwrap))
+    }
+}
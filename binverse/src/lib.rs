@@ -5,10 +5,32 @@
 //! crate, the [`binverse_derive::serializable`] attribute macro automatically
 //! implements the [`Serialize`]/[`Deserialize`] traits
 //! 
+//! # Untrusted input
+//!
+//! Deserializing the types provided by binverse and `#[serializable]` structs
+//! never panics, whatever the input data is. Malformed data, like invalid
+//! UTF-8, an invalid [Option] tag or a truncated stream, results in a
+//! [BinverseError](error::BinverseError). Length prefixes are not trusted for
+//! allocations: collections grow while their elements are read, so a small
+//! input can't cause a huge allocation. The memory used is still
+//! proportional to the size of the input, use
+//! [MessageReader::with_max_len](messages::MessageReader::with_max_len) or
+//! the [bounded] types to limit the size of the input.
+//!
+//! The same applies to skipping values, decoding a `value::Value` using a
+//! type layout and accessing the elements of a [LazyVec](lazy::LazyVec).
+//! This guarantee is checked by the fuzz targets in the `fuzz` directory of
+//! the repository, including the impls for third-party types. Custom
+//! [`Serialize`]/[`Deserialize`] implementations should uphold it as well.
+//!
 //! [`Serialize`]: [serialize::Serialize]
 //! [`Deserialize`]: [serialize::Deserialize]
 
 #![warn(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 /// Serialize/Deserialize traits as well as sized versions of the traits.
 pub mod serialize;
@@ -16,52 +38,150 @@ pub mod serialize;
 pub mod streams;
 /// Serialize/Deserialize implemnentations for primitive types.
 pub mod primitives;
+pub mod std_types;
+pub mod external;
 /// Variable sized integer read/write functions.
 pub mod varint;
 /// BinverseError as well as a BinverseResult type alias.
 pub mod error;
+pub mod io;
+/// Reader and writer for sequences of length-delimited messages.
+#[cfg(feature = "std")]
+pub mod messages;
+/// Append-only log files with random access to the records.
+#[cfg(all(feature = "std", any(unix, windows)))]
+pub mod log;
+pub mod migrate;
+/// Rewrites files written in older revisions at the current revision.
+#[cfg(feature = "std")]
+pub mod upgrade;
+/// Lazy access to serialized collections without deserializing them completely.
+pub mod lazy;
+/// Strings and collections with a capacity that is enforced at construction.
+#[cfg(feature = "alloc")]
+pub mod bounded;
+#[cfg(feature = "alloc")]
+pub mod value;
+/// Round-trip assertion helpers and proptest support for testing
+/// Serialize/Deserialize implementations. Requires the `testing` feature.
+#[cfg(feature = "testing")]
+pub mod testing;
 
 pub use binverse_derive::serializable;
 
-/// Writes a single object to a writer. When writing multiple objects, use [Serializer](streams::Serializer) instead.
+/// Writes a single object to a writer. When writing multiple objects, use [Serializer](streams::Serializer)
+/// or [MessageWriter](messages::MessageWriter) instead.
 /// The revision is also written to the writer for data backwards compatiblity.
 /// 
 /// This is the counterpart to [read()].
-pub fn write<T: serialize::Serialize, W: std::io::Write>(w: W, object: T, current_revision: u32) -> error::BinverseResult<W> {
-    let mut s = streams::Serializer::new(w.into(), current_revision)?;
+pub fn write<T: serialize::Serialize, W: io::Write>(w: W, object: T, current_revision: u32) -> error::BinverseResult<W> {
+    let mut s = streams::Serializer::new(w, current_revision)?;
     object.serialize(&mut s)?;
-    Ok(s.finish())
+    s.finish()
 }
 
-/// Reads a single object from a reader. When reading multiple objects, use [Deserializer](streams::Deserializer) instead.
-/// The revision is also read from the reader so old data can be read.
+/// Reads a single object from a reader. When reading multiple objects, use [Deserializer](streams::Deserializer)
+/// or [MessageReader](messages::MessageReader) instead.
+/// The revision is also read from the reader so old data can be read. The
+/// object is read using [Deserialize::deserialize_root](serialize::Deserialize::deserialize_root),
+/// so types with [migrations](migrate) are converted from the version used in
+/// the revision of the data.
 /// 
 /// This is the counterpart to [write()].
-pub fn read<R: std::io::Read, T: serialize::Deserialize>(r: R) -> error::BinverseResult<(T, R)> {
-    let mut d = streams::Deserializer::new(r.into())?;
-    let t = d.deserialize()?;
+pub fn read<R: io::Read, T: serialize::Deserialize>(r: R) -> error::BinverseResult<(T, R)> {
+    let mut d = streams::Deserializer::new(r)?;
+    let t = T::deserialize_root(&mut d)?;
     Ok((t, d.finish()))
 }
 
-
 /// Writes a single object to a writer without writing the revision. When writing multiple objects, use
 /// [Serializer](streams::Serializer) instead. If you want to be able to parse data in future versions,
 /// use the regular [write()] function.
 /// This can be used when the data won't change in the future or the revision can be implied from context when reading.
 /// 
 /// This is the counterpart to [read_no_revision()].
-pub fn write_no_revision<T: serialize::Serialize, W: std::io::Write>(w: W, object: T) -> error::BinverseResult<W> {
-    let mut s = streams::Serializer::new_no_revision(w.into());
+pub fn write_no_revision<T: serialize::Serialize, W: io::Write>(w: W, object: T) -> error::BinverseResult<W> {
+    let mut s = streams::Serializer::new_no_revision(w);
     object.serialize(&mut s)?;
-    Ok(s.finish())
+    s.finish()
 }
 
 /// Reads a single object from a reader without reading a revision. When reading multiple objects, use
 /// [Deserializer](streams::Deserializer) instead. The `revision` has to be supplied as a parameter.
 /// This can be used when the data won't change in the future or the revision can be implied from context when reading.
 /// This is the counterpart to [write_no_revision].
-pub fn read_no_revision<R: std::io::Read, T: serialize::Deserialize>(r: R, revision: u32) -> error::BinverseResult<(T, R)> {
-    let mut d = streams::Deserializer::new_no_revision(r.into(), revision);
-    let t = d.deserialize()?;
+pub fn read_no_revision<R: io::Read, T: serialize::Deserialize>(r: R, revision: u32) -> error::BinverseResult<(T, R)> {
+    let mut d = streams::Deserializer::new_no_revision(r, revision);
+    let t = T::deserialize_root(&mut d)?;
     Ok((t, d.finish()))
 }
+
+
+/// Computes the number of bytes [write()] will write for an object, including
+/// the revision. Nothing is allocated, so this can be used to find the size of
+/// the buffer needed for [write_slice()].
+pub fn serialized_size<T: serialize::Serialize>(object: &T, current_revision: u32) -> error::BinverseResult<u64> {
+    let mut s = streams::Serializer::new(io::sink(), current_revision)?;
+    object.serialize(&mut s)?;
+    Ok(s.bytes_written())
+}
+
+/// Computes the number of bytes [write_no_revision()] will write for an object.
+/// See [serialized_size()].
+pub fn serialized_size_no_revision<T: serialize::Serialize>(object: &T) -> error::BinverseResult<u64> {
+    let mut s = streams::Serializer::new_no_revision(io::sink());
+    object.serialize(&mut s)?;
+    Ok(s.bytes_written())
+}
+
+/// Computes a hash of an object that is stable across processes and
+/// platforms. The canonical encoding of the object (see
+/// [Serializer::canonical](streams::Serializer::canonical)) is passed to a
+/// new hasher of type `H` without collecting the bytes first, so maps and
+/// sets are hashed in the order of their keys and not in their iteration
+/// order. Like [serialized_size_no_revision()], the object is encoded for the
+/// latest revision and the revision itself is not hashed.
+///
+/// The hash is only as stable as the algorithm of the hasher: std's
+/// `DefaultHasher` may change between Rust versions, use a hasher with a
+/// fixed algorithm like FNV for hashes that are persisted. To hash into an
+/// existing or keyed hasher, use [hash_into()], and for a digest that
+/// implements [Write](io::Write), use [write_canonical()].
+pub fn hash<H: core::hash::Hasher + Default>(object: &(impl serialize::Serialize + ?Sized)) -> error::BinverseResult<u64> {
+    let mut hasher = H::default();
+    hash_into(&mut hasher, object)?;
+    Ok(hasher.finish())
+}
+
+/// Passes the canonical encoding of an object to an existing hasher, see
+/// [hash()]. Hashing multiple objects into the same hasher hashes their
+/// encodings one after another.
+pub fn hash_into<H: core::hash::Hasher>(hasher: &mut H, object: &(impl serialize::Serialize + ?Sized)) -> error::BinverseResult<()> {
+    object.serialize(&mut streams::HashEncoder { hasher })
+}
+
+/// Writes the canonical encoding of an object without the revision, for
+/// example into a digest. Equal objects always produce the same bytes, see
+/// [hash()].
+pub fn write_canonical<T: serialize::Serialize, W: io::Write>(w: W, object: &T) -> error::BinverseResult<W> {
+    let mut s = streams::Serializer::new_no_revision(w).canonical();
+    object.serialize(&mut s)?;
+    s.finish()
+}
+
+/// Writes a single object into a fixed size buffer and returns the number of
+/// bytes used. If the buffer is too small, [BufferFull](error::BinverseError::BufferFull)
+/// is returned. The revision is written like in [write()].
+pub fn write_slice<T: serialize::Serialize>(buf: &mut [u8], object: T, current_revision: u32) -> error::BinverseResult<usize> {
+    let mut s = streams::Serializer::new_slice(buf, current_revision)?;
+    object.serialize(&mut s)?;
+    Ok(s.finish()?.len())
+}
+
+/// Writes a single object into a fixed size buffer without writing the
+/// revision and returns the number of bytes used. See [write_slice()].
+pub fn write_slice_no_revision<T: serialize::Serialize>(buf: &mut [u8], object: T) -> error::BinverseResult<usize> {
+    let mut s = streams::Serializer::new_slice_no_revision(buf);
+    object.serialize(&mut s)?;
+    Ok(s.finish()?.len())
+}
@@ -0,0 +1,433 @@
+use std::{fs::{File, OpenOptions}, io::{self, BufReader, Seek, SeekFrom, Write}, marker::PhantomData, ops::{Bound, RangeBounds}, path::Path};
+#[cfg(unix)]
+use std::os::unix::fs::FileExt;
+#[cfg(windows)]
+use std::os::windows::fs::FileExt;
+
+use crate::{error::{BinverseError, BinverseResult}, messages::checksum, serialize::{Deserialize, Serialize}, streams::{Deserializer, Serializer}, varint::VarInt};
+
+const MAGIC: [u8; 4] = *b"BVLG";
+const FOOTER_MAGIC: [u8; 4] = *b"BVLF";
+const FORMAT_VERSION: u8 = 0;
+const HEADER_LEN: u64 = 5;
+const FOOTER_LEN: u64 = 12;
+
+const TAG_RECORD: u8 = 0;
+const TAG_INDEX: u8 = 1;
+
+/// Marks a missing previous index block.
+const NO_INDEX: u64 = u64::MAX;
+
+/// The default number of records between two index blocks.
+pub const DEFAULT_INDEX_INTERVAL: usize = 1024;
+
+fn is_truncation(err: &BinverseError) -> bool {
+    matches!(err, BinverseError::IO(err) if err.kind() == io::ErrorKind::UnexpectedEof)
+}
+
+/// Reads a file from a position without using the cursor of the file, so
+/// records can be read through a shared reference.
+struct PositionalReader<'a> {
+    file: &'a File,
+    pos: u64
+}
+impl io::Read for PositionalReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        #[cfg(unix)]
+        let n = self.file.read_at(buf, self.pos)?;
+        #[cfg(windows)]
+        let n = self.file.seek_read(buf, self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+fn reader_at(file: &File, pos: u64) -> Deserializer<BufReader<PositionalReader<'_>>> {
+    Deserializer::new_no_revision(BufReader::new(PositionalReader { file, pos }), 0)
+}
+
+/// A frame read while scanning the log.
+enum Frame {
+    Record,
+    Index,
+    /// A frame with a checksum that didn't match its content.
+    Corrupted
+}
+
+/// An append-only log file of records. Every record is stored with the
+/// revision it was written in, so records written in older revisions can still
+/// be read after the record type changed.
+///
+/// The offsets of the records are periodically written to index blocks inside
+/// of the file and [`Log::close`] adds a footer pointing to the last index block.
+/// Opening a closed log only has to read the index blocks, any record can then
+/// be read directly using [`Log::get`].
+///
+/// If the log wasn't closed properly, for example because the process
+/// crashed, all records are scanned when opening it. A partially written
+/// record at the end of the file is detected using a checksum and removed
+/// when the next record is appended. Corrupted frames before the end of the
+/// file are reported as [`BinverseError::InvalidData`] instead.
+///
+/// # File format
+/// - Header: `"BVLG"` followed by a format version byte.
+/// - Record: a `0` tag byte, the revision as a [u32], the payload length as a
+///   varint, the payload and a checksum of the payload.
+/// - Index block: a `1` tag byte, the offset of the previous index block as
+///   a [u64], the number of entries as a varint, the offsets of all records
+///   since the previous index block as [u64]s and a checksum of the block.
+/// - Footer (only present in closed logs): the offset of the last index block
+///   as a [u64] followed by `"BVLF"`.
+pub struct Log<T> {
+    file: File,
+    revision: u32,
+    offsets: Vec<u64>,
+    last_index: u64,
+    unindexed: usize,
+    index_interval: usize,
+    end: u64,
+    /// The length of the file, which is larger than `end` if there is a footer
+    /// or a partially written frame that is removed before the next write.
+    file_len: u64,
+    _marker: PhantomData<fn() -> T>
+}
+
+impl<T> Log<T> {
+    /// Opens the log file at `path` or creates it if it doesn't exist. New
+    /// records will be written with `revision`.
+    pub fn open<P: AsRef<Path>>(path: P, revision: u32) -> BinverseResult<Self> {
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        let len = file.metadata()?.len();
+        if len == 0 {
+            let mut s = Serializer::new_no_revision(&mut file);
+            s.write(&MAGIC)?;
+            FORMAT_VERSION.serialize(&mut s)?;
+        } else {
+            let mut d = reader_at(&file, 0);
+            let magic: [u8; 4] = d.deserialize()?;
+            let version: u8 = d.deserialize()?;
+            if magic != MAGIC || version != FORMAT_VERSION {
+                return Err(BinverseError::InvalidData);
+            }
+        }
+        let mut log = Self {
+            file,
+            revision,
+            offsets: Vec::new(),
+            last_index: NO_INDEX,
+            unindexed: 0,
+            index_interval: DEFAULT_INDEX_INTERVAL,
+            end: HEADER_LEN,
+            file_len: len.max(HEADER_LEN),
+            _marker: PhantomData
+        };
+        if len > HEADER_LEN {
+            let footer = log.read_footer(len)?;
+            let data_end = if footer.is_some() { len - FOOTER_LEN } else { len };
+            let loaded = match footer {
+                Some(last_index) => log.load_index(last_index, data_end)?,
+                None => false
+            };
+            if !loaded {
+                log.recover(data_end)?;
+            }
+        }
+        Ok(log)
+    }
+
+    /// Sets the number of records after which an index block is written.
+    ///
+    /// # Panics
+    /// Panics if the interval is 0.
+    pub fn with_index_interval(mut self, interval: usize) -> Self {
+        assert!(interval > 0, "Index interval has to be at least 1");
+        self.index_interval = interval;
+        self
+    }
+
+    /// The revision new records are written with.
+    pub fn revision(&self) -> u32 { self.revision }
+
+    /// The number of records in the log.
+    pub fn len(&self) -> usize { self.offsets.len() }
+
+    /// Returns true if the log doesn't contain any records.
+    pub fn is_empty(&self) -> bool { self.offsets.is_empty() }
+
+    /// Returns the offset of the last index block stored in the footer of a
+    /// closed log, or `None` if there is no footer.
+    fn read_footer(&self, len: u64) -> BinverseResult<Option<u64>> {
+        if len < HEADER_LEN + FOOTER_LEN {
+            return Ok(None);
+        }
+        let mut d = reader_at(&self.file, len - FOOTER_LEN);
+        let last_index: u64 = d.deserialize()?;
+        let magic: [u8; 4] = d.deserialize()?;
+        Ok((magic == FOOTER_MAGIC).then_some(last_index))
+    }
+
+    /// Reads the index blocks starting at the last one. Returns false if the
+    /// index blocks are damaged, the log then has to be scanned.
+    fn load_index(&mut self, last_index: u64, end: u64) -> BinverseResult<bool> {
+        let mut blocks = Vec::new();
+        let mut index = last_index;
+        while index != NO_INDEX {
+            match self.read_index_block(index, end) {
+                Ok((prev, offsets)) => {
+                    blocks.push(offsets);
+                    index = prev;
+                }
+                Err(BinverseError::IO(err)) if err.kind() != io::ErrorKind::UnexpectedEof => return Err(err.into()),
+                Err(_) => return Ok(false)
+            }
+        }
+        self.offsets = blocks.into_iter().rev().flatten().collect();
+        self.last_index = last_index;
+        // the footer is overwritten by the next write
+        self.end = end;
+        Ok(true)
+    }
+
+    fn read_index_block(&self, offset: u64, end: u64) -> BinverseResult<(u64, Vec<u64>)> {
+        if offset < HEADER_LEN || offset >= end {
+            return Err(BinverseError::InvalidData);
+        }
+        let mut d = reader_at(&self.file, offset);
+        if d.deserialize::<u8>()? != TAG_INDEX {
+            return Err(BinverseError::InvalidData);
+        }
+        read_index_body(&mut d, offset, end)?.ok_or(BinverseError::InvalidData)
+    }
+
+    /// Scans all frames of a log that wasn't closed. A frame that was cut off
+    /// or has a wrong checksum at the end of the file is the result of an
+    /// interrupted write and is ignored, it's removed by the next write.
+    fn recover(&mut self, len: u64) -> BinverseResult<()> {
+        let mut d = reader_at(&self.file, HEADER_LEN);
+        let mut offsets = Vec::new();
+        let mut unindexed = 0;
+        let mut last_index = NO_INDEX;
+        let mut end = HEADER_LEN;
+        while end < len {
+            let offset = end;
+            let frame = d.deserialize::<u8>().and_then(|tag| match tag {
+                TAG_RECORD => read_record_body(&mut d, len - offset)
+                    .map(|record| if record.is_some() { Frame::Record } else { Frame::Corrupted }),
+                TAG_INDEX => read_index_body(&mut d, offset, len)
+                    .map(|index| if index.is_some() { Frame::Index } else { Frame::Corrupted }),
+                _ => Err(BinverseError::InvalidData)
+            });
+            match frame {
+                Ok(Frame::Record) => {
+                    offsets.push(offset);
+                    unindexed += 1;
+                }
+                Ok(Frame::Index) => {
+                    last_index = offset;
+                    unindexed = 0;
+                }
+                // only the last frame can be partially written
+                Ok(Frame::Corrupted) if HEADER_LEN + d.bytes_read() == len => break,
+                Ok(Frame::Corrupted) => return Err(BinverseError::InvalidData),
+                Err(err) if is_truncation(&err) => break,
+                // a partially written footer
+                Err(BinverseError::InvalidData) if len - offset < FOOTER_LEN => break,
+                Err(err) => return Err(err)
+            }
+            end = HEADER_LEN + d.bytes_read();
+        }
+        self.offsets = offsets;
+        self.unindexed = unindexed;
+        self.last_index = last_index;
+        self.end = end;
+        Ok(())
+    }
+
+    fn write_frame(&mut self, frame: &[u8]) -> BinverseResult<()> {
+        if self.file_len > self.end {
+            self.file.set_len(self.end)?;
+        }
+        self.file.seek(SeekFrom::Start(self.end))?;
+        self.file.write_all(frame)?;
+        self.end += frame.len() as u64;
+        self.file_len = self.end;
+        Ok(())
+    }
+
+    /// Writes an index block with the offsets of all records since the last one.
+    fn write_index(&mut self) -> BinverseResult<()> {
+        let offset = self.end;
+        let mut body = Vec::new();
+        let mut s = Serializer::new_no_revision(&mut body);
+        self.last_index.serialize(&mut s)?;
+        VarInt(self.unindexed as u64).serialize(&mut s)?;
+        for offset in &self.offsets[self.offsets.len() - self.unindexed..] {
+            offset.serialize(&mut s)?;
+        }
+        let mut frame = Vec::with_capacity(body.len() + 5);
+        let mut s = Serializer::new_no_revision(&mut frame);
+        TAG_INDEX.serialize(&mut s)?;
+        s.write(&body)?;
+        checksum(&body).serialize(&mut s)?;
+        self.write_frame(&frame)?;
+        self.last_index = offset;
+        self.unindexed = 0;
+        Ok(())
+    }
+
+    /// Flushes all written records to the disk.
+    pub fn sync(&mut self) -> BinverseResult<()> {
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// Writes a final index block and the footer and syncs the file. Logs that
+    /// are dropped without being closed are still valid, but have to be
+    /// scanned completely when opened the next time.
+    pub fn close(mut self) -> BinverseResult<()> {
+        self.write_index()?;
+        let mut footer = Vec::with_capacity(FOOTER_LEN as usize);
+        let mut s = Serializer::new_no_revision(&mut footer);
+        self.last_index.serialize(&mut s)?;
+        s.write(&FOOTER_MAGIC)?;
+        self.write_frame(&footer)?;
+        self.sync()
+    }
+
+    fn read_record(&self, n: usize) -> BinverseResult<Option<(u32, Vec<u8>)>> {
+        let offset = match self.offsets.get(n) {
+            Some(offset) => *offset,
+            None => return Ok(None)
+        };
+        let mut d = reader_at(&self.file, offset);
+        if d.deserialize::<u8>()? != TAG_RECORD {
+            return Err(BinverseError::InvalidData);
+        }
+        read_record_body(&mut d, self.end - offset)?.ok_or(BinverseError::InvalidData).map(Some)
+    }
+}
+
+/// Reads the revision and payload of a record frame after the tag and
+/// verifies the checksum, returns `None` if it doesn't match. `max_len` is
+/// the maximum number of bytes the frame can take up.
+fn read_record_body<R: io::Read>(d: &mut Deserializer<R>, max_len: u64) -> BinverseResult<Option<(u32, Vec<u8>)>> {
+    let revision: u32 = d.deserialize()?;
+    let len = d.deserialize::<VarInt>()?.0;
+    if len > max_len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    let mut payload = vec![0; len as usize];
+    d.read(&mut payload)?;
+    if d.deserialize::<u32>()? != checksum(&payload) {
+        return Ok(None);
+    }
+    Ok(Some((revision, payload)))
+}
+
+/// Reads an index block after the tag and verifies the checksum, returns
+/// `None` if it doesn't match.
+fn read_index_body<R: io::Read>(d: &mut Deserializer<R>, offset: u64, end: u64) -> BinverseResult<Option<(u64, Vec<u64>)>> {
+    let prev: u64 = d.deserialize()?;
+    let count = d.deserialize::<VarInt>()?.0;
+    // every entry takes 8 bytes, a larger count can't fit into the file
+    if count > (end - offset) / 8 {
+        return Err(BinverseError::InvalidData);
+    }
+    let offsets = (0..count).map(|_| d.deserialize()).collect::<BinverseResult<Vec<u64>>>()?;
+    let mut body = Vec::with_capacity(8 * (offsets.len() + 1) + 10);
+    let mut s = Serializer::new_no_revision(&mut body);
+    prev.serialize(&mut s)?;
+    VarInt(count).serialize(&mut s)?;
+    for offset in &offsets {
+        offset.serialize(&mut s)?;
+    }
+    if d.deserialize::<u32>()? != checksum(&body) {
+        return Ok(None);
+    }
+    Ok(Some((prev, offsets)))
+}
+
+impl<T: Serialize> Log<T> {
+    /// Appends a record to the end of the log and returns its index.
+    /// The record isn't guaranteed to be on the disk until [`Log::sync`] or
+    /// [`Log::close`] is called.
+    pub fn append(&mut self, record: &T) -> BinverseResult<usize> {
+        let mut payload = Vec::new();
+        record.serialize(&mut Serializer::new_no_revision(&mut payload).for_revision(self.revision))?;
+        let mut frame = Vec::with_capacity(payload.len() + 15);
+        let mut s = Serializer::new_no_revision(&mut frame);
+        TAG_RECORD.serialize(&mut s)?;
+        self.revision.serialize(&mut s)?;
+        VarInt(payload.len() as u64).serialize(&mut s)?;
+        s.write(&payload)?;
+        checksum(&payload).serialize(&mut s)?;
+
+        let offset = self.end;
+        self.write_frame(&frame)?;
+        self.offsets.push(offset);
+        self.unindexed += 1;
+        if self.unindexed >= self.index_interval {
+            self.write_index()?;
+        }
+        Ok(self.offsets.len() - 1)
+    }
+}
+
+impl<T: Deserialize> Log<T> {
+    /// Reads the record with index `n`. The record is deserialized using the
+    /// revision it was written in. Returns `None` if there is no record with
+    /// this index.
+    pub fn get(&self, n: usize) -> BinverseResult<Option<T>> {
+        self.read_record(n)?.map(|(revision, payload)| {
+            let mut d = Deserializer::new_no_revision(payload.as_slice(), revision);
+            let cp = d.checkpoint();
+            let t = d.deserialize()?;
+            d.verify_len(cp, payload.len() as u64)?;
+            Ok(t)
+        }).transpose()
+    }
+
+    /// Iterates over all records of the log.
+    pub fn iter(&self) -> LogIter<'_, T> {
+        self.range(..)
+    }
+
+    /// Iterates over the records in the index range.
+    pub fn range<B: RangeBounds<usize>>(&self, range: B) -> LogIter<'_, T> {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.saturating_add(1),
+            Bound::Unbounded => 0
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end.saturating_add(1),
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => self.len()
+        };
+        LogIter { log: self, next: start, end: end.min(self.len()) }
+    }
+}
+
+/// An iterator over records of a [Log], created by [`Log::iter`] and [`Log::range`].
+pub struct LogIter<'a, T> {
+    log: &'a Log<T>,
+    next: usize,
+    end: usize
+}
+impl<'a, T: Deserialize> Iterator for LogIter<'a, T> {
+    type Item = BinverseResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+        let n = self.next;
+        self.next += 1;
+        self.log.get(n).transpose()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end.saturating_sub(self.next);
+        (len, Some(len))
+    }
+}
@@ -0,0 +1,269 @@
+use std::{collections::VecDeque, io::{self, Read, Write}, marker::PhantomData, mem, ops::Range};
+
+use crate::{error::{BinverseError, BinverseResult}, serialize::{Deserialize, Serialize}, streams::{Deserializer, Serializer}, varint};
+
+const CHECKSUM_LEN: u64 = 4;
+
+/// 32-bit FNV-1a hash used to detect corrupted and partially written frames.
+pub(crate) fn checksum(bytes: &[u8]) -> u32 {
+    checksum_update(0x811c9dc5, bytes)
+}
+
+fn checksum_update(hash: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(hash, |hash, b| (hash ^ *b as u32).wrapping_mul(0x01000193))
+}
+
+/// Writes a sequence of objects as length-delimited messages. The revision is
+/// written once at the start of the stream, every message is prefixed with its
+/// length in bytes as a varint and followed by a checksum of its bytes.
+///
+/// This is the counterpart to [MessageReader].
+pub struct MessageWriter<W: Write> {
+    w: W,
+    revision: u32,
+    buf: Vec<u8>
+}
+impl<W: Write> MessageWriter<W> {
+    /// Creates a new message writer and writes the revision to the writer.
+    pub fn new(w: W, revision: u32) -> BinverseResult<Self> {
+        let s = Serializer::new(w, revision)?;
+        Ok(Self { w: s.finish()?, revision, buf: Vec::new() })
+    }
+
+    /// Serializes a single message and writes it with a length prefix.
+    /// Nothing is written to the underlying writer if serialization fails.
+    pub fn write<T: Serialize>(&mut self, message: &T) -> BinverseResult<()> {
+        self.buf.clear();
+        let mut s = Serializer::new_no_revision(&mut self.buf).for_revision(self.revision);
+        message.serialize(&mut s)?;
+        varint::write(self.buf.len() as u64, &mut self.w)?;
+        self.w.write_all(&self.buf)?;
+        self.w.write_all(&checksum(&self.buf).to_le_bytes())?;
+        Ok(())
+    }
+
+    /// The revision the messages are written with.
+    pub fn revision(&self) -> u32 { self.revision }
+
+    /// Flushes the writer and returns it.
+    pub fn finish(mut self) -> BinverseResult<W> {
+        self.w.flush()?;
+        Ok(self.w)
+    }
+}
+
+/// Reads length-delimited messages written by a [MessageWriter]. Messages are
+/// returned by iterating over the reader, the iterator ends when the
+/// underlying reader ends at a message boundary.
+///
+/// Because every message is read in full before it is deserialized, an error
+/// while deserializing a message doesn't affect the following messages and
+/// iterating can simply continue. The same applies to messages longer than
+/// the maximum length, they are skipped.
+///
+/// A corrupted frame, for example a damaged length or a message whose
+/// checksum doesn't match, is returned as an error once. The reader then
+/// resyncs by searching the following bytes for the next frame with a
+/// matching checksum, so iteration continues with the next intact message.
+/// Messages overlapping the corrupted bytes are lost, and so are messages
+/// longer than the maximum length while searching. Other IO errors end the
+/// iteration.
+pub struct MessageReader<R: Read, T: Deserialize> {
+    input: Input<R>,
+    revision: u32,
+    max_len: u64,
+    resyncing: bool,
+    done: bool,
+    _marker: PhantomData<fn() -> T>
+}
+impl<R: Read, T: Deserialize> MessageReader<R, T> {
+    /// Creates a new message reader and reads the revision from the reader.
+    pub fn new(r: R) -> BinverseResult<Self> {
+        let d = Deserializer::new(r)?;
+        let revision = d.revision();
+        Ok(Self {
+            input: Input { r: d.finish(), pending: VecDeque::new(), frame: Vec::new(), record: true },
+            revision,
+            max_len: u64::MAX,
+            resyncing: false,
+            done: false,
+            _marker: PhantomData
+        })
+    }
+
+    /// Sets the maximum accepted length of a single message in bytes. A
+    /// longer message is skipped without reading it into memory and results
+    /// in a [`BinverseError::MessageTooLarge`] error, iteration continues with
+    /// the next message. There is no limit by default.
+    pub fn with_max_len(mut self, max_len: u64) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// The revision of the messages being read.
+    pub fn revision(&self) -> u32 { self.revision }
+
+    /// Returns the inner reader. Bytes that were read ahead while resyncing
+    /// are lost.
+    pub fn finish(self) -> R { self.input.r }
+
+    /// Reads the next frame and returns the range of its payload in
+    /// `self.input.frame`, or `None` if the reader ended at a message
+    /// boundary. Frames longer than the maximum length are skipped and
+    /// returned as [`FrameError::TooLarge`], unless the reader is resyncing.
+    fn read_frame(&mut self) -> Result<Option<Range<usize>>, FrameError> {
+        self.input.frame.clear();
+        let mut first = [0];
+        loop {
+            match self.input.read(&mut first) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(FrameError::Io(e.into()))
+            }
+        }
+        let len = varint::read(first.as_slice().chain(&mut self.input)).map_err(FrameError::from)?;
+        let start = self.input.frame.len();
+        if len > self.max_len {
+            if self.resyncing {
+                return Err(FrameError::Corrupt(BinverseError::InvalidData));
+            }
+            // the skipped bytes aren't kept, the checksum is computed while skipping
+            self.input.record = false;
+            let skipped = skip(&mut self.input, len);
+            self.input.record = true;
+            let hash = skipped?;
+            self.read_checksum(hash)?;
+            return Err(FrameError::TooLarge(BinverseError::MessageTooLarge { limit: self.max_len, found: len }));
+        }
+        // the payload is kept in the frame buffer, which grows incrementally
+        // so a corrupted length can't cause a huge allocation
+        let read = io::copy(&mut (&mut self.input).take(len), &mut io::sink()).map_err(|e| FrameError::from(BinverseError::from(e)))?;
+        if read < len {
+            return Err(FrameError::Corrupt(io::Error::from(io::ErrorKind::UnexpectedEof).into()));
+        }
+        let payload = start..self.input.frame.len();
+        self.read_checksum(checksum(&self.input.frame[payload.clone()]))?;
+        Ok(Some(payload))
+    }
+
+    fn read_checksum(&mut self, expected: u32) -> Result<(), FrameError> {
+        let mut bytes = [0; CHECKSUM_LEN as usize];
+        self.input.read_exact(&mut bytes).map_err(|e| FrameError::from(BinverseError::from(e)))?;
+        if u32::from_le_bytes(bytes) != expected {
+            return Err(FrameError::Corrupt(BinverseError::InvalidData));
+        }
+        Ok(())
+    }
+}
+
+/// Skips `len` bytes and returns their checksum.
+fn skip(r: &mut impl Read, mut len: u64) -> Result<u32, FrameError> {
+    const CHUNK: usize = 4096;
+    let mut buf = [0; CHUNK];
+    let mut hash = checksum(&[]);
+    while len > 0 {
+        match r.read(&mut buf[..len.min(CHUNK as u64) as usize]) {
+            Ok(0) => return Err(FrameError::Corrupt(io::Error::from(io::ErrorKind::UnexpectedEof).into())),
+            Ok(n) => {
+                hash = checksum_update(hash, &buf[..n]);
+                len -= n as u64;
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(FrameError::Io(e.into()))
+        }
+    }
+    Ok(hash)
+}
+
+/// The underlying reader of a [MessageReader]. Keeps the bytes of the
+/// current frame so they can be searched for the next frame if it turns out
+/// to be corrupted.
+struct Input<R> {
+    r: R,
+    /// Bytes that were read from `r` but not consumed yet.
+    pending: VecDeque<u8>,
+    /// The bytes read since the start of the current frame.
+    frame: Vec<u8>,
+    record: bool
+}
+impl<R: Read> Input<R> {
+    /// Returns the bytes of the current frame after its first byte, so the
+    /// next frame is searched starting at the following byte.
+    fn rewind(&mut self) {
+        let frame = mem::take(&mut self.frame);
+        for b in frame.iter().skip(1).rev() {
+            self.pending.push_front(*b);
+        }
+    }
+}
+impl<R: Read> Read for Input<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = if self.pending.is_empty() {
+            self.r.read(buf)?
+        } else {
+            self.pending.read(buf)?
+        };
+        if self.record {
+            self.frame.extend_from_slice(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+/// An error while reading a frame.
+enum FrameError {
+    /// The frame was skipped, the next frame can be read.
+    TooLarge(BinverseError),
+    /// The frame is corrupted or truncated, the next frame has to be searched.
+    Corrupt(BinverseError),
+    /// Reading failed, iteration can't continue.
+    Io(BinverseError)
+}
+impl From<BinverseError> for FrameError {
+    fn from(err: BinverseError) -> Self {
+        match err {
+            BinverseError::IO(e) if e.kind() != io::ErrorKind::UnexpectedEof => Self::Io(e.into()),
+            err => Self::Corrupt(err)
+        }
+    }
+}
+impl<R: Read, T: Deserialize> Iterator for MessageReader<R, T> {
+    type Item = BinverseResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let payload = loop {
+            match self.read_frame() {
+                Ok(Some(payload)) => break payload,
+                Ok(None) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(FrameError::TooLarge(err)) => return Some(Err(err)),
+                Err(FrameError::Corrupt(err)) => {
+                    self.input.rewind();
+                    // only the first error of a corrupted section is returned
+                    if !self.resyncing {
+                        self.resyncing = true;
+                        return Some(Err(err));
+                    }
+                }
+                Err(FrameError::Io(err)) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        };
+        self.resyncing = false;
+        let payload = &self.input.frame[payload];
+        let mut d = Deserializer::new_no_revision(payload, self.revision);
+        let cp = d.checkpoint();
+        Some(d.deserialize().and_then(|t| {
+            d.verify_len(cp, payload.len() as u64)?;
+            Ok(t)
+        }))
+    }
+}
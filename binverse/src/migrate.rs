@@ -0,0 +1,164 @@
+//! Migrations between versions of a root type that can't be expressed with
+//! the field-level `Added`/`Removed` attributes, like splitting a struct or
+//! changing a [Vec] into a map.
+//!
+//! The old versions are kept as separate types, each one is used for a range
+//! of revisions and converted to the next newer version using [From] until
+//! the current type is reached. Adding `migrate` to the attribute,
+//! `#[serializable(migrate)]`, makes [read()](crate::read) and the
+//! [Upgrader](crate::upgrade::Upgrader) read the type using its [Migrate]
+//! impl:
+//!
+//! ```
+//! use binverse::{migrate::{Migrate, Migrations}, serializable, streams::Decoder, error::BinverseResult};
+//!
+//! #[serializable]
+//! struct V1 { names: Vec<String> }
+//!
+//! #[serializable]
+//! struct V2 { names: Vec<String>, counts: Vec<u32> }
+//! impl From<V1> for V2 {
+//!     fn from(v1: V1) -> Self {
+//!         let counts = vec![0; v1.names.len()];
+//!         Self { names: v1.names, counts }
+//!     }
+//! }
+//!
+//! #[serializable(migrate)]
+//! struct Current { entries: Vec<(String, u32)> }
+//! impl From<V2> for Current {
+//!     fn from(v2: V2) -> Self {
+//!         Self { entries: v2.names.into_iter().zip(v2.counts).collect() }
+//!     }
+//! }
+//!
+//! impl Migrate for Current {
+//!     fn deserialize_migrated<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+//!         // V1 for 0..=3, V2 for 4..=7, Current for all later revisions
+//!         Migrations::new()
+//!             .revisions::<V2>(4..=7)
+//!             .revisions::<V1>(0..=3)
+//!             .deserialize(d)
+//!     }
+//! }
+//!
+//! let data = binverse::write(Vec::new(), V1 { names: vec!["a".to_owned()] }, 2).unwrap();
+//! let (current, _): (Current, _) = binverse::read(data.as_slice()).unwrap();
+//! assert_eq!(current.entries, [("a".to_owned(), 0)]);
+//! ```
+//!
+//! Migrations only apply to the root object. A migrated type that is a
+//! field of another type is always read as the current type, the revision
+//! of the data describes the layout of the root type.
+
+use core::{marker::PhantomData, ops::RangeInclusive};
+
+use crate::{error::BinverseResult, serialize::Deserialize, streams::Decoder};
+
+/// Implemented for root types that can be read from data written with older
+/// versions of the type. Usually implemented using [Migrations]. The impl is
+/// used by [Deserialize::deserialize_root], which `#[serializable(migrate)]`
+/// generates, or which can be overridden manually to call
+/// [Migrate::deserialize_migrated].
+pub trait Migrate: Deserialize {
+    /// Deserializes the version of the type that was used in the revision
+    /// of the deserializer and converts it to the current type.
+    fn deserialize_migrated<D: Decoder>(d: &mut D) -> BinverseResult<Self>;
+}
+
+/// A chain of type versions built by [Migrations] that can be converted to
+/// the current type `T`.
+pub trait Chain<T> {
+    /// The oldest version in the chain.
+    type Oldest;
+
+    /// Converts a value of the oldest version to the current type.
+    fn upgrade(oldest: Self::Oldest) -> T;
+
+    /// Deserializes the version used in the revision of the deserializer
+    /// and converts it to the current type.
+    fn deserialize<D: Decoder>(&self, d: &mut D) -> BinverseResult<T>;
+}
+
+/// The end of a [Chain], the current type itself.
+pub struct Current<T>(PhantomData<fn() -> T>);
+impl<T: Deserialize> Chain<T> for Current<T> {
+    type Oldest = T;
+
+    fn upgrade(oldest: T) -> T { oldest }
+
+    fn deserialize<D: Decoder>(&self, d: &mut D) -> BinverseResult<T> {
+        T::deserialize(d)
+    }
+}
+
+/// An older version `O` in a [Chain] that is used for a range of revisions
+/// and converted to the oldest version of the newer chain `C`.
+pub struct Older<O, C> {
+    revisions: RangeInclusive<u32>,
+    newer: C,
+    _marker: PhantomData<fn() -> O>
+}
+impl<T, O: Deserialize, C: Chain<T>> Chain<T> for Older<O, C>
+where C::Oldest: From<O> {
+    type Oldest = O;
+
+    fn upgrade(oldest: O) -> T {
+        C::upgrade(C::Oldest::from(oldest))
+    }
+
+    fn deserialize<D: Decoder>(&self, d: &mut D) -> BinverseResult<T> {
+        if self.revisions.contains(&d.revision()) {
+            O::deserialize(d).map(Self::upgrade)
+        } else {
+            self.newer.deserialize(d)
+        }
+    }
+}
+
+/// Builds the chain of versions of a root type `T`. Older versions are
+/// registered from the newest to the oldest with the revisions they were
+/// used in, each version has to be convertible to the previously registered
+/// one using [From]. Revisions that aren't registered are read as the
+/// current type.
+pub struct Migrations<T, C = Current<T>> {
+    chain: C,
+    oldest_revision: Option<u32>,
+    _marker: PhantomData<fn() -> T>
+}
+impl<T: Deserialize> Migrations<T> {
+    /// Creates a chain that only contains the current type.
+    pub fn new() -> Self {
+        Self { chain: Current(PhantomData), oldest_revision: None, _marker: PhantomData }
+    }
+}
+impl<T: Deserialize> Default for Migrations<T> {
+    fn default() -> Self { Self::new() }
+}
+impl<T, C: Chain<T>> Migrations<T, C> {
+    /// Registers the version `O` for the revisions. `O` is converted to the
+    /// previously registered version, or the current type if this is the
+    /// first one.
+    ///
+    /// # Panics
+    /// Panics if the revisions are empty or not older than the revisions
+    /// registered before.
+    pub fn revisions<O: Deserialize>(self, revisions: RangeInclusive<u32>) -> Migrations<T, Older<O, C>>
+    where C::Oldest: From<O> {
+        assert!(!revisions.is_empty(), "Empty revision range for a migration");
+        if let Some(oldest) = self.oldest_revision {
+            assert!(*revisions.end() < oldest, "Migrations have to be registered from the newest to the oldest revisions");
+        }
+        Migrations {
+            oldest_revision: Some(*revisions.start()),
+            chain: Older { revisions, newer: self.chain, _marker: PhantomData },
+            _marker: PhantomData
+        }
+    }
+
+    /// Deserializes the version used in the revision of the deserializer and
+    /// converts it to the current type.
+    pub fn deserialize<D: Decoder>(&self, d: &mut D) -> BinverseResult<T> {
+        self.chain.deserialize(d)
+    }
+}
@@ -1,56 +1,277 @@
-use std::{io::{Read, Write}, collections::HashMap, hash::Hash, mem::{ManuallyDrop, MaybeUninit}};
+use core::{mem::MaybeUninit, num::{NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating, Wrapping}};
+use core::sync::atomic::{self, Ordering};
+#[cfg(feature = "alloc")]
+use alloc::{collections::{BTreeMap, BTreeSet}, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{collections::{HashMap, HashSet}, hash::Hash};
 
-use crate::{error::{BinverseError, BinverseResult}, serialize::{Deserialize, Serialize, SizeBytes, SizedDeserialize, SizedSerialize}, streams::{Deserializer, Serializer}};
+use crate::{error::{BinverseError, BinverseResult}, serialize::{Deserialize, FixedSize, Serialize, SizeBytes, SizedSerialize}, streams::{Decoder, Encoder}, varint};
+#[cfg(feature = "alloc")]
+use crate::streams::sub_serializer;
+#[cfg(feature = "alloc")]
+use crate::serialize::SizedDeserialize;
 
 impl Serialize for bool {
-    fn serialize<W: Write>(&self, s: &mut Serializer<W>) -> BinverseResult<()> {
-        s.write(&[*self as u8])
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.write_u8(*self as u8)
     }
 }
+impl FixedSize for bool {
+    const SIZE: usize = 1;
+}
 impl Deserialize for bool {
-    fn deserialize<R: Read>(d: &mut Deserializer<R>) -> BinverseResult<Self> {
-        let mut buf = [0];
-        d.read(&mut buf)?;
-        match buf[0] {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        match d.read_u8()? {
             0 => Ok(false),
             1 => Ok(true),
             _ => Err(BinverseError::InvalidData)
         }
     }
+    fn skip<D: Decoder>(d: &mut D) -> BinverseResult<()> {
+        d.skip(1)
+    }
+    fn skip_many<D: Decoder>(d: &mut D, n: usize) -> BinverseResult<()> {
+        d.skip(n as u64)
+    }
 }
 
+/// The number of bytes taken up by `n` values of `size` bytes.
+fn skip_len(n: usize, size: u64) -> BinverseResult<u64> {
+    (n as u64).checked_mul(size).ok_or(BinverseError::IntegerOverflow)
+}
 
 macro_rules! number_impls {
-    ($($t: ty, $bytes: expr),*) => {
+    ($($t: ty, $bytes: expr, $write: ident, $read: ident),*) => {
         $(
             impl Serialize for $t {
-                fn serialize<W: Write>(&self, s: &mut Serializer<W>) -> BinverseResult<()> {
-                    s.write(&self.to_le_bytes())
+                fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+                    s.$write(*self)
                 }
             }
             impl Deserialize for $t {
-                fn deserialize<R: Read>(d: &mut Deserializer<R>) -> BinverseResult<Self> {
-                    let mut b = [0; $bytes];
-                    d.read(&mut b)?;
-                    Ok(<$t>::from_le_bytes(b))
+                fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+                    d.$read()
                 }
+                fn skip<D: Decoder>(d: &mut D) -> BinverseResult<()> {
+                    d.skip($bytes)
+                }
+                fn skip_many<D: Decoder>(d: &mut D, n: usize) -> BinverseResult<()> {
+                    d.skip(skip_len(n, $bytes)?)
+                }
+            }
+            impl FixedSize for $t {
+                const SIZE: usize = $bytes;
             }
         )*
     };
 }
 number_impls!(
-    u8, 1, i8, 1,
-    u16, 2, i16, 2,
-    u32, 4, i32, 4,
-    u64, 8, i64, 8,
-    u128, 16, i128, 16,
-    f32, 4,
-    f64, 8
+    u8, 1, write_u8, read_u8, i8, 1, write_i8, read_i8,
+    u16, 2, write_u16, read_u16, i16, 2, write_i16, read_i16,
+    u32, 4, write_u32, read_u32, i32, 4, write_i32, read_i32,
+    u64, 8, write_u64, read_u64, i64, 8, write_i64, read_i64,
+    u128, 16, write_u128, read_u128, i128, 16, write_i128, read_i128
+);
+
+macro_rules! float_impls {
+    ($($t: ty, $bytes: expr, $canonical_nan: expr, $write: ident, $read: ident),*) => {
+        $(
+            impl Serialize for $t {
+                fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+                    if s.is_canonical() && self.is_nan() {
+                        s.$write(<$t>::from_bits($canonical_nan))
+                    } else {
+                        s.$write(*self)
+                    }
+                }
+            }
+            impl Deserialize for $t {
+                fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+                    let x = d.$read()?;
+                    if d.is_strict() && x.is_nan() && x.to_bits() != $canonical_nan {
+                        return Err(BinverseError::NonCanonical);
+                    }
+                    Ok(x)
+                }
+                fn skip<D: Decoder>(d: &mut D) -> BinverseResult<()> {
+                    d.skip($bytes)
+                }
+                fn skip_many<D: Decoder>(d: &mut D, n: usize) -> BinverseResult<()> {
+                    d.skip(skip_len(n, $bytes)?)
+                }
+            }
+            impl FixedSize for $t {
+                const SIZE: usize = $bytes;
+            }
+        )*
+    };
+}
+// the canonical NaNs are the quiet NaNs without a sign or payload
+float_impls!(
+    f32, 4, 0x7fc0_0000_u32, write_f32, read_f32,
+    f64, 8, 0x7ff8_0000_0000_0000_u64, write_f64, read_f64
+);
+
+// usize/isize are written as varints because their size is platform dependent.
+// isize uses zigzag encoding so small negative numbers stay short.
+impl Serialize for usize {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.write_varint(*self as u64)
+    }
+}
+impl Deserialize for usize {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        usize::try_from(d.read_varint()?).map_err(|_| BinverseError::IntegerOverflow)
+    }
+    fn skip<D: Decoder>(d: &mut D) -> BinverseResult<()> {
+        d.read_varint().map(drop)
+    }
+}
+impl Serialize for isize {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.write_varint(varint::zigzag(*self as i64))
+    }
+}
+impl Deserialize for isize {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        isize::try_from(varint::unzigzag(d.read_varint()?)).map_err(|_| BinverseError::IntegerOverflow)
+    }
+    fn skip<D: Decoder>(d: &mut D) -> BinverseResult<()> {
+        d.read_varint().map(drop)
+    }
+}
+
+impl Serialize for char {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        (*self as u32).serialize(s)
+    }
+}
+impl Deserialize for char {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        let x = d.deserialize()?;
+        char::from_u32(x).ok_or(BinverseError::InvalidChar(x))
+    }
+    fn skip<D: Decoder>(d: &mut D) -> BinverseResult<()> {
+        u32::skip(d)
+    }
+    fn skip_many<D: Decoder>(d: &mut D, n: usize) -> BinverseResult<()> {
+        u32::skip_many(d, n)
+    }
+}
+impl FixedSize for char {
+    const SIZE: usize = 4;
+}
+
+macro_rules! non_zero_impls {
+    ($($t: ty),*) => {
+        $(
+            impl Serialize for $t {
+                fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+                    self.get().serialize(s)
+                }
+            }
+            impl Deserialize for $t {
+                fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+                    <$t>::new(d.deserialize()?).ok_or(BinverseError::ZeroValue)
+                }
+            }
+        )*
+    };
+}
+non_zero_impls!(
+    NonZeroU8, NonZeroI8, NonZeroU16, NonZeroI16, NonZeroU32, NonZeroI32,
+    NonZeroU64, NonZeroI64, NonZeroU128, NonZeroI128, NonZeroUsize, NonZeroIsize
+);
+macro_rules! non_zero_fixed_size {
+    ($($t: ty, $inner: ty),*) => {
+        $(
+            impl FixedSize for $t {
+                const SIZE: usize = <$inner>::SIZE;
+            }
+        )*
+    };
+}
+non_zero_fixed_size!(
+    NonZeroU8, u8, NonZeroI8, i8, NonZeroU16, u16, NonZeroI16, i16,
+    NonZeroU32, u32, NonZeroI32, i32, NonZeroU64, u64, NonZeroI64, i64,
+    NonZeroU128, u128, NonZeroI128, i128
+);
+
+macro_rules! wrapper_impls {
+    ($($t: ident),*) => {
+        $(
+            impl<T: Serialize> Serialize for $t<T> {
+                fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+                    self.0.serialize(s)
+                }
+            }
+            impl<T: Deserialize> Deserialize for $t<T> {
+                fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+                    d.deserialize().map($t)
+                }
+                fn skip<D: Decoder>(d: &mut D) -> BinverseResult<()> {
+                    T::skip(d)
+                }
+                fn skip_many<D: Decoder>(d: &mut D, n: usize) -> BinverseResult<()> {
+                    T::skip_many(d, n)
+                }
+            }
+            impl<T: FixedSize> FixedSize for $t<T> {
+                const SIZE: usize = T::SIZE;
+            }
+        )*
+    };
+}
+wrapper_impls!(Wrapping, Saturating);
+
+// Atomics serialize a snapshot of their value loaded with SeqCst ordering.
+macro_rules! atomic_impls {
+    ($($t: ty, $inner: ty, $cfg: literal),*) => {
+        $(
+            #[cfg(target_has_atomic = $cfg)]
+            impl Serialize for $t {
+                fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+                    self.load(Ordering::SeqCst).serialize(s)
+                }
+            }
+            #[cfg(target_has_atomic = $cfg)]
+            impl Deserialize for $t {
+                fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+                    d.deserialize::<$inner>().map(<$t>::new)
+                }
+            }
+        )*
+    };
+}
+atomic_impls!(
+    atomic::AtomicBool, bool, "8",
+    atomic::AtomicU8, u8, "8", atomic::AtomicI8, i8, "8",
+    atomic::AtomicU16, u16, "16", atomic::AtomicI16, i16, "16",
+    atomic::AtomicU32, u32, "32", atomic::AtomicI32, i32, "32",
+    atomic::AtomicU64, u64, "64", atomic::AtomicI64, i64, "64",
+    atomic::AtomicUsize, usize, "ptr", atomic::AtomicIsize, isize, "ptr"
+);
+macro_rules! atomic_fixed_size {
+    ($($t: ty, $inner: ty, $cfg: literal),*) => {
+        $(
+            #[cfg(target_has_atomic = $cfg)]
+            impl FixedSize for $t {
+                const SIZE: usize = <$inner>::SIZE;
+            }
+        )*
+    };
+}
+atomic_fixed_size!(
+    atomic::AtomicBool, bool, "8",
+    atomic::AtomicU8, u8, "8", atomic::AtomicI8, i8, "8",
+    atomic::AtomicU16, u16, "16", atomic::AtomicI16, i16, "16",
+    atomic::AtomicU32, u32, "32", atomic::AtomicI32, i32, "32",
+    atomic::AtomicU64, u64, "64", atomic::AtomicI64, i64, "64"
 );
 
 impl<T, const N: usize> Serialize for [T; N]
 where T: Serialize {
-    fn serialize<W: Write>(&self, s: &mut Serializer<W>) -> BinverseResult<()> {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
         for elem in self {
             elem.serialize(s)?;
         }
@@ -59,49 +280,54 @@ where T: Serialize {
 }
 
 /// To initialize an array element by element, we need an array containing
-/// uninitialized data. InitializingArray does this but only drops the elements
-/// that are initalized to prevent it from dropping invalid data if it is
-/// dropped before it is fully initialized.
+/// uninitialized data. InitializingArray stores the elements as
+/// [MaybeUninit] and only drops the elements that are initialized, so
+/// elements read before an error or a panic are dropped correctly.
 struct InitializingArray<T, const N: usize> {
-    inner: ManuallyDrop<[T; N]>,
+    inner: [MaybeUninit<T>; N],
     initialized_to: usize
 }
 impl<T, const N: usize> InitializingArray<T, N> {
     fn new() -> Self {
         Self {
-            inner: unsafe { MaybeUninit::uninit().assume_init() },
+            // SAFETY: an array of MaybeUninit doesn't require initialization.
+            inner: unsafe { MaybeUninit::<[MaybeUninit<T>; N]>::uninit().assume_init() },
             initialized_to: 0
         }
     }
     fn push(&mut self, t: T) {
         assert!(self.initialized_to < N);
-        unsafe { std::ptr::write(&mut self.inner[self.initialized_to], t) };
+        self.inner[self.initialized_to].write(t);
         self.initialized_to += 1;
     }
     fn get(self) -> [T; N] {
-        assert_eq!(self.inner.len(), self.initialized_to);
-        // SAFETY: we prevent the object from dropping by manually pulling out the fields.
-        // Because we get all the fields, this is safe.
-        // The array is also guaranteed to be fully initialized because of the assert above;
-        unsafe {
-            let inner = std::ptr::read(&self.inner);
-            let _initialized_to = std::ptr::read(&self.initialized_to);
-            std::mem::forget(self);
-            ManuallyDrop::into_inner(inner)
-        }
+        assert_eq!(N, self.initialized_to);
+        // SAFETY: all elements are initialized because of the assert above and
+        // MaybeUninit<T> has the same layout as T. Forgetting self afterwards
+        // prevents the elements from being dropped twice.
+        let arr = unsafe { (&self.inner as *const [MaybeUninit<T>; N]).cast::<[T; N]>().read() };
+        core::mem::forget(self);
+        arr
     }
 }
 impl<T, const N: usize> Drop for InitializingArray<T, N> {
     fn drop(&mut self) {
-        // only the initialized data is dropped
+        // SAFETY: only the initialized elements are dropped
         unsafe {
-            std::ptr::drop_in_place(std::slice::from_raw_parts_mut(self.inner.as_mut_ptr(), self.initialized_to));
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.inner.as_mut_ptr().cast::<T>(),
+                self.initialized_to
+            ));
         }
     }
 }
 
+impl<T: FixedSize, const N: usize> FixedSize for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+}
+
 impl<T: Deserialize, const N: usize> Deserialize for [T; N] {
-    fn deserialize<R: Read>(d: &mut Deserializer<R>) -> BinverseResult<Self> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
         let mut init_arr = InitializingArray::new();
         for _ in 0..N {
             let x = d.deserialize()?;
@@ -109,10 +335,19 @@ impl<T: Deserialize, const N: usize> Deserialize for [T; N] {
         }
         Ok(init_arr.get())
     }
+    fn skip<D: Decoder>(d: &mut D) -> BinverseResult<()> {
+        T::skip_many(d, N)
+    }
+    fn deserialize_in_place<D: Decoder>(&mut self, d: &mut D) -> BinverseResult<()> {
+        self.iter_mut().try_for_each(|elem| elem.deserialize_in_place(d))
+    }
+    fn skip_many<D: Decoder>(d: &mut D, n: usize) -> BinverseResult<()> {
+        T::skip_many(d, n.checked_mul(N).ok_or(BinverseError::IntegerOverflow)?)
+    }
 }
 
 impl<T: Serialize> Serialize for Option<T> {
-    fn serialize<W: Write>(&self, s: &mut Serializer<W>) -> BinverseResult<()> {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
         if let Some(e) = self {
             1_u8.serialize(s)?;
             e.serialize(s)?;
@@ -123,13 +358,29 @@ impl<T: Serialize> Serialize for Option<T> {
     }
 }
 impl<T: Deserialize> Deserialize for Option<T> {
-    fn deserialize<R: Read>(d: &mut Deserializer<R>) -> BinverseResult<Self> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
         Ok(match d.deserialize()? {
             0_u8 => None,
             1_u8 => Some(d.deserialize()?),
             _ => return Err(BinverseError::InvalidData)
         })
     }
+    fn skip<D: Decoder>(d: &mut D) -> BinverseResult<()> {
+        match d.deserialize()? {
+            0_u8 => Ok(()),
+            1_u8 => T::skip(d),
+            _ => Err(BinverseError::InvalidData)
+        }
+    }
+    fn deserialize_in_place<D: Decoder>(&mut self, d: &mut D) -> BinverseResult<()> {
+        match (d.deserialize()?, self.as_mut()) {
+            (0_u8, _) => *self = None,
+            (1_u8, Some(t)) => t.deserialize_in_place(d)?,
+            (1_u8, None) => *self = Some(d.deserialize()?),
+            _ => return Err(BinverseError::InvalidData)
+        }
+        Ok(())
+    }
 }
 
 // tuples
@@ -137,15 +388,22 @@ macro_rules! tuples {
     ($($($t: ident $elem: tt)*;)*) => {
         $(
             impl<$($t: Serialize),*> Serialize for ($($t),*) {
-                fn serialize<W: Write>(&self, s: &mut Serializer<W>) -> BinverseResult<()> {
+                fn serialize<Enc: Encoder>(&self, s: &mut Enc) -> BinverseResult<()> {
                     $( self.$elem.serialize(s)?; )*
                     Ok(())
                 }
             }
             impl<$($t: Deserialize),*> Deserialize for ($($t),*) {
-                fn deserialize<R: Read>(d: &mut Deserializer<R>) -> BinverseResult<Self> {
+                fn deserialize<Dec: Decoder>(d: &mut Dec) -> BinverseResult<Self> {
                     Ok(($( <$t as $crate::serialize::Deserialize>::deserialize(d)?, )*))
                 }
+                fn skip<Dec: Decoder>(d: &mut Dec) -> BinverseResult<()> {
+                    $( $t::skip(d)?; )*
+                    Ok(())
+                }
+            }
+            impl<$($t: FixedSize),*> FixedSize for ($($t),*) {
+                const SIZE: usize = 0 $( + $t::SIZE )*;
             }
         )*
     }
@@ -163,17 +421,44 @@ tuples! {
     A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9;
     A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 K 10;
     A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 K 10 L 11;
-    A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 K 10 L 11 M 11;
-    A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 K 10 L 11 M 11 N 12;
-    A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 K 10 L 11 M 11 N 12 O 13;
-    A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 K 10 L 11 M 11 N 12 O 13 P 14;
-    A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 K 10 L 11 M 11 N 12 O 13 P 14 Q 15;
+    A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 K 10 L 11 M 12;
+    A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 K 10 L 11 M 12 N 13;
+    A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 K 10 L 11 M 12 N 13 O 14;
+    A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 K 10 L 11 M 12 N 13 O 14 P 15;
+    A 0 B 1 C 2 D 3 E 4 F 5 G 6 H 7 I 8 J 9 K 10 L 11 M 12 N 13 O 14 P 15 Q 16;
 }
 
 // str/String
+
+/// The maximum number of bytes allocated at once by [read_bytes].
+#[cfg(feature = "alloc")]
+const READ_CHUNK: usize = 4096;
+
+/// Reads `size` bytes into a [Vec]. The size comes from untrusted data, so
+/// the buffer grows while reading instead of allocating it completely upfront.
+#[cfg(feature = "alloc")]
+pub(crate) fn read_bytes<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Vec<u8>> {
+    let mut b = Vec::new();
+    read_bytes_into(d, &mut b, size)?;
+    Ok(b)
+}
+
+/// Like [read_bytes], but replaces the contents of an existing buffer.
+#[cfg(feature = "alloc")]
+fn read_bytes_into<D: Decoder>(d: &mut D, b: &mut Vec<u8>, size: usize) -> BinverseResult<()> {
+    b.clear();
+    b.reserve(size.min(READ_CHUNK));
+    while b.len() < size {
+        let start = b.len();
+        b.resize(start + (size - start).min(READ_CHUNK), 0);
+        d.read(&mut b[start..])?;
+    }
+    Ok(())
+}
+
 impl SizedSerialize for &str {
-    fn serialize_sized<W: Write>(&self, s: &mut Serializer<W>, size: usize) -> BinverseResult<()> {
-        s.write(self[..size].as_bytes())
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+        s.write(self.get(..size).ok_or(BinverseError::InvalidUTF8)?.as_bytes())
     }
     fn size(&self) -> usize {
         self.len()
@@ -185,21 +470,28 @@ macro_rules! ser_sized {
         $(
             impl<$($generic),*> Serialize for $t
             where $($($tree)*)* {
-                fn serialize<W: Write>(&self, s: &mut Serializer<W>) -> BinverseResult<()> {
+                fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
                     s.serialize_sized(SizeBytes::Var, self)
                 }
             }
         )*
     }
 }
+#[cfg(feature = "alloc")]
 macro_rules! deser_sized {
     ($({$t: ty [$($generic: tt),*] [$([$($tree: tt)*]),*]})*) => {
         $(
             impl<$($generic),*> Deserialize for $t
             where $($($tree)*)* {
-                fn deserialize<R: Read>(d: &mut Deserializer<R>) -> BinverseResult<Self> {
+                fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
                     d.deserialize_sized(SizeBytes::Var)
                 }
+                fn skip<D: Decoder>(d: &mut D) -> BinverseResult<()> {
+                    d.skip_sized::<Self>(SizeBytes::Var)
+                }
+                fn deserialize_in_place<D: Decoder>(&mut self, d: &mut D) -> BinverseResult<()> {
+                    d.deserialize_sized_in_place(self, SizeBytes::Var)
+                }
             }
         )*
     }
@@ -207,36 +499,61 @@ macro_rules! deser_sized {
 
 ser_sized!{
     {&str [][]}
-    {String [][]}
     {&[T] [T] [[T: Serialize]]}
+}
+#[cfg(feature = "alloc")]
+ser_sized!{
+    {String [][]}
     {Vec<T> [T] [[T: Serialize]]}
-    {HashMap<K, V> [K, V] [[K: Serialize, V: Serialize]]}
+    {BTreeMap<K, V> [K, V] [[K: Serialize, V: Serialize]]}
+    {BTreeSet<T> [T] [[T: Serialize]]}
 }
+#[cfg(feature = "alloc")]
 deser_sized!{
     {String [][]}
     {Vec<T> [T] [[T: Deserialize]]}
+    {BTreeMap<K, V> [K, V] [[K: Deserialize + Ord, V: Deserialize]]}
+    {BTreeSet<T> [T] [[T: Deserialize + Ord]]}
+}
+#[cfg(feature = "std")]
+ser_sized!{
+    {HashMap<K, V> [K, V] [[K: Serialize, V: Serialize]]}
+    {HashSet<T> [T] [[T: Serialize]]}
+}
+#[cfg(feature = "std")]
+deser_sized!{
     {HashMap<K, V> [K, V] [[K: Deserialize + Eq + Hash, V: Deserialize]]}
+    {HashSet<T> [T] [[T: Deserialize + Eq + Hash]]}
 }
 
+#[cfg(feature = "alloc")]
 impl SizedSerialize for String {
-    fn serialize_sized<W: Write>(&self, s: &mut Serializer<W>, size: usize) -> BinverseResult<()> {
-        // TODO: slicing here could make code more inefficient when the size is usually correct
-        s.write(self[..size].as_bytes())
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+        self.as_str().serialize_sized(s, size)
     }
     fn size(&self) -> usize { self.len() }
 }
+#[cfg(feature = "alloc")]
 impl SizedDeserialize for String {
-    fn deserialize_sized<R: Read>(d: &mut Deserializer<R>, size: usize) -> BinverseResult<Self> {
-        let mut b = vec![0; size];
-        d.read(&mut b)?;
-        String::from_utf8(b).or(Err(BinverseError::InvalidUTF8))
+    fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self> {
+        String::from_utf8(read_bytes(d, size)?).or(Err(BinverseError::InvalidUTF8))
+    }
+    fn skip_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<()> {
+        d.skip(size as u64)
+    }
+    fn deserialize_sized_in_place<D: Decoder>(&mut self, d: &mut D, size: usize) -> BinverseResult<()> {
+        // the string stays empty if reading fails
+        let mut b = core::mem::take(self).into_bytes();
+        read_bytes_into(d, &mut b, size)?;
+        *self = String::from_utf8(b).or(Err(BinverseError::InvalidUTF8))?;
+        Ok(())
     }
 }
 
 impl<T> SizedSerialize for &[T]
 where T: Serialize {
-    fn serialize_sized<W: Write>(&self, s: &mut Serializer<W>, size: usize) -> BinverseResult<()> {
-        for elem in &self[0..size] {
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+        for elem in self.get(..size).ok_or(BinverseError::InvalidData)? {
             elem.serialize(s)?;
         }
         Ok(())
@@ -245,22 +562,93 @@ where T: Serialize {
         self.len()
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: Serialize> SizedSerialize for Vec<T> {
-    fn serialize_sized<W: Write>(&self, s: &mut Serializer<W>, size: usize) -> BinverseResult<()> {
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
         self.as_slice().serialize_sized(s, size)
     }
     fn size(&self) -> usize {
         self.len()
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: Deserialize> SizedDeserialize for Vec<T> {
-    fn deserialize_sized<R: Read>(d: &mut Deserializer<R>, size: usize) -> BinverseResult<Self> {
+    fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self> {
         (0..size).map(|_| d.deserialize()).collect::<BinverseResult<Vec<_>>>()
     }
+    fn skip_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<()> {
+        T::skip_many(d, size)
+    }
+    fn deserialize_sized_in_place<D: Decoder>(&mut self, d: &mut D, size: usize) -> BinverseResult<()> {
+        self.truncate(size);
+        for elem in self.iter_mut() {
+            elem.deserialize_in_place(d)?;
+        }
+        while self.len() < size {
+            self.push(d.deserialize()?);
+        }
+        Ok(())
+    }
+}
+
+/// Sorts map or set entries by their keys serialized in the binverse format.
+/// Used to write maps and sets in canonical mode.
+#[cfg(feature = "alloc")]
+fn sorted_entries<'a, K: Serialize + 'a, V>(s: &impl Encoder, entries: impl Iterator<Item = (&'a K, V)>) -> BinverseResult<Vec<(&'a K, V)>> {
+    let mut sorted = entries.map(|(k, v)| {
+        let mut key_s = sub_serializer(s, Vec::new());
+        k.serialize(&mut key_s)?;
+        Ok((key_s.finish()?, k, v))
+    }).collect::<BinverseResult<Vec<_>>>()?;
+    sorted.sort_unstable_by(|(a, _, _), (b, _, _)| a.cmp(b));
+    Ok(sorted.into_iter().map(|(_, k, v)| (k, v)).collect())
+}
+
+/// A decoder that records the bytes read from another decoder. Wrapping a
+/// `dyn Decoder` keeps the type the same for nested maps.
+#[cfg(feature = "alloc")]
+struct Capturing<'a> {
+    d: &'a mut dyn Decoder,
+    bytes: Vec<u8>
+}
+#[cfg(feature = "alloc")]
+impl Decoder for Capturing<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> BinverseResult<()> {
+        self.d.read(buf)?;
+        self.bytes.extend_from_slice(buf);
+        Ok(())
+    }
+    fn revision(&self) -> u32 { self.d.revision() }
+    fn is_strict(&self) -> bool { self.d.is_strict() }
+}
+
+/// Deserializes the key of a map or set entry. In strict mode, the serialized
+/// key has to be larger than the previous one.
+#[cfg(feature = "alloc")]
+fn deserialize_key<D: Decoder, K: Deserialize>(d: &mut D, prev: &mut Option<Vec<u8>>) -> BinverseResult<K> {
+    if !d.is_strict() {
+        return d.deserialize();
+    }
+    let mut capturing = Capturing { d, bytes: Vec::new() };
+    let k = capturing.deserialize()?;
+    let bytes = capturing.bytes;
+    if prev.as_ref().is_some_and(|prev| *prev >= bytes) {
+        return Err(BinverseError::NonCanonical);
+    }
+    *prev = Some(bytes);
+    Ok(k)
 }
 
+#[cfg(feature = "std")]
 impl<K: Serialize, V: Serialize> SizedSerialize for HashMap<K, V> {
-    fn serialize_sized<W: Write>(&self, s: &mut Serializer<W>, size: usize) -> BinverseResult<()> {
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+        if s.is_canonical() {
+            for (k, v) in sorted_entries(s, self.iter().take(size))? {
+                k.serialize(s)?;
+                v.serialize(s)?;
+            }
+            return Ok(());
+        }
         for (k, v) in self.iter().take(size) {
             k.serialize(s)?;
             v.serialize(s)?;
@@ -271,8 +659,126 @@ impl<K: Serialize, V: Serialize> SizedSerialize for HashMap<K, V> {
         self.len()
     }
 }
+#[cfg(feature = "std")]
 impl<K: Deserialize + Eq + Hash, V: Deserialize> SizedDeserialize for HashMap<K, V> {
-    fn deserialize_sized<R: Read>(d: &mut Deserializer<R>, size: usize) -> BinverseResult<Self> {
-        Ok((0..size).map(|_| Ok((d.deserialize()?, d.deserialize()?))).collect::<BinverseResult<HashMap<K, V>>>()?)
+    fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self> {
+        let mut prev = None;
+        (0..size).map(|_| Ok((deserialize_key(d, &mut prev)?, d.deserialize()?))).collect::<BinverseResult<HashMap<K, V>>>()
+    }
+    fn skip_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<()> {
+        (0..size).try_for_each(|_| {
+            K::skip(d)?;
+            V::skip(d)
+        })
+    }
+    fn deserialize_sized_in_place<D: Decoder>(&mut self, d: &mut D, size: usize) -> BinverseResult<()> {
+        // draining keeps the table, the old values are reused for the new
+        // entries independent of their keys
+        let mut slots: Vec<V> = self.drain().map(|(_, v)| v).collect();
+        let mut prev = None;
+        for _ in 0..size {
+            let k = deserialize_key(d, &mut prev)?;
+            let v = match slots.pop() {
+                Some(mut v) => {
+                    v.deserialize_in_place(d)?;
+                    v
+                }
+                None => d.deserialize()?
+            };
+            self.insert(k, v);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Serialize> SizedSerialize for HashSet<T> {
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+        if s.is_canonical() {
+            for (t, ()) in sorted_entries(s, self.iter().take(size).map(|t| (t, ())))? {
+                t.serialize(s)?;
+            }
+            return Ok(());
+        }
+        for t in self.iter().take(size) {
+            t.serialize(s)?;
+        }
+        Ok(())
+    }
+    fn size(&self) -> usize {
+        self.len()
+    }
+}
+#[cfg(feature = "std")]
+impl<T: Deserialize + Eq + Hash> SizedDeserialize for HashSet<T> {
+    fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self> {
+        let mut prev = None;
+        (0..size).map(|_| deserialize_key(d, &mut prev)).collect::<BinverseResult<HashSet<T>>>()
+    }
+    fn skip_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<()> {
+        T::skip_many(d, size)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Serialize, V: Serialize> SizedSerialize for BTreeMap<K, V> {
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+        if s.is_canonical() {
+            for (k, v) in sorted_entries(s, self.iter().take(size))? {
+                k.serialize(s)?;
+                v.serialize(s)?;
+            }
+            return Ok(());
+        }
+        for (k, v) in self.iter().take(size) {
+            k.serialize(s)?;
+            v.serialize(s)?;
+        }
+        Ok(())
+    }
+    fn size(&self) -> usize {
+        self.len()
+    }
+}
+#[cfg(feature = "alloc")]
+impl<K: Deserialize + Ord, V: Deserialize> SizedDeserialize for BTreeMap<K, V> {
+    fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self> {
+        let mut prev = None;
+        (0..size).map(|_| Ok((deserialize_key(d, &mut prev)?, d.deserialize()?))).collect::<BinverseResult<BTreeMap<K, V>>>()
+    }
+    fn skip_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<()> {
+        (0..size).try_for_each(|_| {
+            K::skip(d)?;
+            V::skip(d)
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Serialize> SizedSerialize for BTreeSet<T> {
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()> {
+        if s.is_canonical() {
+            for (t, ()) in sorted_entries(s, self.iter().take(size).map(|t| (t, ())))? {
+                t.serialize(s)?;
+            }
+            return Ok(());
+        }
+        for t in self.iter().take(size) {
+            t.serialize(s)?;
+        }
+        Ok(())
+    }
+    fn size(&self) -> usize {
+        self.len()
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Deserialize + Ord> SizedDeserialize for BTreeSet<T> {
+    fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self> {
+        let mut prev = None;
+        (0..size).map(|_| deserialize_key(d, &mut prev)).collect::<BinverseResult<BTreeSet<T>>>()
+    }
+    fn skip_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<()> {
+        T::skip_many(d, size)
     }
 }
@@ -1,13 +1,13 @@
-use std::io::{Read, Write};
-use crate::{error::BinverseResult, streams::{Deserializer, Serializer}};
+use crate::{error::BinverseResult, streams::{Decoder, Encoder}};
 
 /// The Serialize trait provides a function to serialize into a data stream.
 /// It can be implemented manually or by using the #\[binverse_derive::serializable\] attribute.
 pub trait Serialize {
     /// The serialize function.
     /// Arguments:
-    /// - `s` - The serializer that the data will be written to.
-    fn serialize<W: Write>(&self, s: &mut Serializer<W>) -> BinverseResult<()>;
+    /// - `s` - The encoder that the data will be written to, usually a
+    ///   [Serializer](crate::streams::Serializer).
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()>;
 }
 
 /// The deserialize trait provides a function to deserialize from a data
@@ -16,13 +16,47 @@ pub trait Serialize {
 pub trait Deserialize : Sized {
     /// The deserialize function.
     /// Arguments:
-    /// - `d` - The deserializer that the data will be read from.
-    fn deserialize<R: Read>(d: &mut Deserializer<R>) -> BinverseResult<Self>;
+    /// - `d` - The decoder that the data will be read from, usually a
+    ///   [Deserializer](crate::streams::Deserializer).
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self>;
+
+    /// Advances past a value without keeping it, for example to skip removed
+    /// fields in old data. The default implementation deserializes the value
+    /// and drops it. Types with a known size override it to skip the bytes
+    /// directly, the skipped data is not validated then.
+    fn skip<D: Decoder>(d: &mut D) -> BinverseResult<()> {
+        Self::deserialize(d).map(drop)
+    }
+
+    /// Skips `n` consecutive values. Fixed size types override it to skip
+    /// all values at once.
+    fn skip_many<D: Decoder>(d: &mut D, n: usize) -> BinverseResult<()> {
+        (0..n).try_for_each(|_| Self::skip(d))
+    }
+
+    /// Deserializes a root object, used by [read()](crate::read) and the
+    /// other functions reading a single object with its revision. The default
+    /// implementation calls [Deserialize::deserialize]. Types that changed in
+    /// ways the field attributes can't express override it to read the
+    /// version of the type used in the revision of the data, see
+    /// [migrate](crate::migrate).
+    fn deserialize_root<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        Self::deserialize(d)
+    }
+
+    /// Deserializes into an existing value, which lets types like [Vec] and
+    /// [String] reuse their allocations. The default implementation
+    /// deserializes a new value and replaces `self`. If an error is
+    /// returned, `self` may be partially overwritten.
+    fn deserialize_in_place<D: Decoder>(&mut self, d: &mut D) -> BinverseResult<()> {
+        *self = Self::deserialize(d)?;
+        Ok(())
+    }
 }
 
 /// An enum representing the possible lengths of the size bytes for a variable
 /// length data structure.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum SizeBytes {
     /// The length is serialized using a single byte ([u8]).
     One,
@@ -53,6 +87,21 @@ impl SizeBytes {
         }
     }
 
+    /// Returns the smallest fixed SizeBytes variant that can store lengths up
+    /// to `max`.
+    pub const fn smallest_for(max: u64) -> Self {
+        use SizeBytes::*;
+        if max <= u8::MAX as u64 {
+            One
+        } else if max <= u16::MAX as u64 {
+            Two
+        } else if max <= u32::MAX as u64 {
+            Four
+        } else {
+            Eight
+        }
+    }
+
     /// Returns the maximum possible length available with a SizeBytes variant.
     pub const fn maximum(&self) -> u64 {
         use SizeBytes::*;
@@ -73,7 +122,7 @@ pub trait SizedSerialize : Serialize {
     /// Arguments:
     /// - `s` - The serializer that the data will be written to.
     /// - `size` - The number of elements to write.
-    fn serialize_sized<W: Write>(&self, s: &mut Serializer<W>, size: usize) -> BinverseResult<()>;
+    fn serialize_sized<E: Encoder>(&self, s: &mut E, size: usize) -> BinverseResult<()>;
 
     /// Should return the current number of elements of the data structure.
     fn size(&self) -> usize;
@@ -87,5 +136,68 @@ pub trait SizedDeserialize : Deserialize + Sized {
     /// Arguments:
     /// - `d` - The deserializer that the data will be written to.
     /// - `size` - The number of elements to read
-    fn deserialize_sized<R: Read>(d: &mut Deserializer<R>, size: usize) -> BinverseResult<Self>;
+    fn deserialize_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<Self>;
+
+    /// Skips `size` elements without keeping them, see [`Deserialize::skip`].
+    /// To skip a length stored in the data first, use
+    /// [crate::streams::Deserializer::skip_sized].
+    fn skip_sized<D: Decoder>(d: &mut D, size: usize) -> BinverseResult<()> {
+        Self::deserialize_sized(d, size).map(drop)
+    }
+
+    /// Reads `size` elements into an existing instance, see
+    /// [`Deserialize::deserialize_in_place`].
+    fn deserialize_sized_in_place<D: Decoder>(&mut self, d: &mut D, size: usize) -> BinverseResult<()> {
+        *self = Self::deserialize_sized(d, size)?;
+        Ok(())
+    }
+}
+
+/// Implemented for types that always serialize to the same number of bytes,
+/// independent of their value and the revision. This makes it possible to
+/// compute the position of an element in a serialized collection directly,
+/// see [crate::lazy::LazyVec].
+/// It can be implemented manually for structs that only contain fixed size
+/// fields, `SIZE` then is the sum of the field sizes.
+pub trait FixedSize {
+    /// The number of bytes a value of this type serializes to.
+    const SIZE: usize;
+}
+
+/// Support for writing fields marked as `Removed` in revisions that still
+/// contain them. Used by the code generated by `#[serializable]`.
+#[doc(hidden)]
+pub mod removed {
+    use core::marker::PhantomData;
+    use crate::error::{BinverseError, BinverseResult};
+
+    /// Selects the value to write for a removed field of type `T`. The
+    /// generated code calls `(&RemovedField::<T>::new()).value()`, which
+    /// resolves to [WithDefault] if `T` implements [Default] and to
+    /// [WithoutDefault] otherwise.
+    pub struct RemovedField<T>(PhantomData<T>);
+    impl<T> RemovedField<T> {
+        #[allow(clippy::new_without_default)]
+        pub fn new() -> Self {
+            Self(PhantomData)
+        }
+    }
+
+    pub trait WithDefault<T> {
+        fn value(&self) -> BinverseResult<T>;
+    }
+    impl<T: Default> WithDefault<T> for RemovedField<T> {
+        fn value(&self) -> BinverseResult<T> {
+            Ok(T::default())
+        }
+    }
+
+    pub trait WithoutDefault<T> {
+        fn value(&self) -> BinverseResult<T>;
+    }
+    impl<T> WithoutDefault<T> for &RemovedField<T> {
+        fn value(&self) -> BinverseResult<T> {
+            Err(BinverseError::RemovedFieldWithoutDefault)
+        }
+    }
 }
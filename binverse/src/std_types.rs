@@ -0,0 +1,246 @@
+//! Encodings of common standard library value types. All of them are
+//! validated when reading:
+//!
+//! - [Duration]: seconds as `u64` and nanoseconds as `u32`. Nanoseconds of
+//!   one second or more are rejected.
+//! - [SystemTime]: the signed offset from the unix epoch, seconds as `i64`
+//!   and nanoseconds as `u32`. Times before the epoch have negative seconds
+//!   and the nanoseconds are added, so `-0.25s` is written as `-1s + 750000000ns`.
+//! - [Ipv4Addr]/[Ipv6Addr]: the 4 or 16 octets. [IpAddr] and [SocketAddr]
+//!   prefix them with a tag byte, 0 for v4 and 1 for v6.
+//! - [SocketAddrV4]: address and port. [SocketAddrV6] additionally writes
+//!   the flow info and scope id as `u32`.
+//! - [PathBuf]/[Path]: like a [String]. Paths that aren't valid UTF-8 can't
+//!   be serialized portably and return [`BinverseError::InvalidUTF8`].
+//! - [Range]/[RangeInclusive]: start followed by end.
+//! - [Result]: a tag byte, 0 for `Ok` and 1 for `Err`, followed by the value.
+
+use core::{net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6}, ops::{Range, RangeInclusive}, time::Duration};
+#[cfg(feature = "std")]
+use std::{path::{Path, PathBuf}, string::String, time::{SystemTime, UNIX_EPOCH}};
+
+use crate::{error::{BinverseError, BinverseResult}, serialize::{Deserialize, FixedSize, Serialize}, streams::{Decoder, Encoder}};
+#[cfg(feature = "std")]
+use crate::serialize::SizeBytes;
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+fn read_nanos<D: Decoder>(d: &mut D) -> BinverseResult<u32> {
+    let nanos = d.deserialize()?;
+    if nanos >= NANOS_PER_SEC {
+        return Err(BinverseError::InvalidData);
+    }
+    Ok(nanos)
+}
+
+impl Serialize for Duration {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        self.as_secs().serialize(s)?;
+        self.subsec_nanos().serialize(s)
+    }
+}
+impl Deserialize for Duration {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        let secs = d.deserialize()?;
+        Ok(Duration::new(secs, read_nanos(d)?))
+    }
+}
+impl FixedSize for Duration {
+    const SIZE: usize = 12;
+}
+
+#[cfg(feature = "std")]
+impl Serialize for SystemTime {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        let secs = |d: Duration| i64::try_from(d.as_secs()).map_err(|_| BinverseError::IntegerOverflow);
+        let (secs, nanos) = match self.duration_since(UNIX_EPOCH) {
+            Ok(after) => (secs(after)?, after.subsec_nanos()),
+            Err(err) => {
+                let before = err.duration();
+                match before.subsec_nanos() {
+                    0 => (-secs(before)?, 0),
+                    nanos => (-secs(before)? - 1, NANOS_PER_SEC - nanos)
+                }
+            }
+        };
+        secs.serialize(s)?;
+        nanos.serialize(s)
+    }
+}
+#[cfg(feature = "std")]
+impl Deserialize for SystemTime {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        let secs: i64 = d.deserialize()?;
+        let nanos = Duration::from_nanos(read_nanos(d)?.into());
+        let time = if secs >= 0 {
+            UNIX_EPOCH.checked_add(Duration::from_secs(secs as u64))
+        } else {
+            UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs()))
+        };
+        // times that can't be represented on this platform
+        time.and_then(|t| t.checked_add(nanos)).ok_or(BinverseError::IntegerOverflow)
+    }
+}
+#[cfg(feature = "std")]
+impl FixedSize for SystemTime {
+    const SIZE: usize = 12;
+}
+
+impl Serialize for Ipv4Addr {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.write(&self.octets())
+    }
+}
+impl Deserialize for Ipv4Addr {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        d.deserialize::<[u8; 4]>().map(Ipv4Addr::from)
+    }
+}
+impl FixedSize for Ipv4Addr {
+    const SIZE: usize = 4;
+}
+impl Serialize for Ipv6Addr {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.write(&self.octets())
+    }
+}
+impl Deserialize for Ipv6Addr {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        d.deserialize::<[u8; 16]>().map(Ipv6Addr::from)
+    }
+}
+impl FixedSize for Ipv6Addr {
+    const SIZE: usize = 16;
+}
+impl Serialize for IpAddr {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        match self {
+            IpAddr::V4(ip) => { 0_u8.serialize(s)?; ip.serialize(s) }
+            IpAddr::V6(ip) => { 1_u8.serialize(s)?; ip.serialize(s) }
+        }
+    }
+}
+impl Deserialize for IpAddr {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        Ok(match d.deserialize()? {
+            0_u8 => IpAddr::V4(d.deserialize()?),
+            1_u8 => IpAddr::V6(d.deserialize()?),
+            _ => return Err(BinverseError::InvalidData)
+        })
+    }
+}
+
+impl Serialize for SocketAddrV4 {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        self.ip().serialize(s)?;
+        self.port().serialize(s)
+    }
+}
+impl Deserialize for SocketAddrV4 {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        Ok(SocketAddrV4::new(d.deserialize()?, d.deserialize()?))
+    }
+}
+impl FixedSize for SocketAddrV4 {
+    const SIZE: usize = 6;
+}
+impl Serialize for SocketAddrV6 {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        self.ip().serialize(s)?;
+        self.port().serialize(s)?;
+        self.flowinfo().serialize(s)?;
+        self.scope_id().serialize(s)
+    }
+}
+impl Deserialize for SocketAddrV6 {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        Ok(SocketAddrV6::new(d.deserialize()?, d.deserialize()?, d.deserialize()?, d.deserialize()?))
+    }
+}
+impl FixedSize for SocketAddrV6 {
+    const SIZE: usize = 26;
+}
+impl Serialize for SocketAddr {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        match self {
+            SocketAddr::V4(addr) => { 0_u8.serialize(s)?; addr.serialize(s) }
+            SocketAddr::V6(addr) => { 1_u8.serialize(s)?; addr.serialize(s) }
+        }
+    }
+}
+impl Deserialize for SocketAddr {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        Ok(match d.deserialize()? {
+            0_u8 => SocketAddr::V4(d.deserialize()?),
+            1_u8 => SocketAddr::V6(d.deserialize()?),
+            _ => return Err(BinverseError::InvalidData)
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl Serialize for &Path {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        let path = self.to_str().ok_or(BinverseError::InvalidUTF8)?;
+        s.serialize_sized(SizeBytes::Var, &path)
+    }
+}
+#[cfg(feature = "std")]
+impl Serialize for PathBuf {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        self.as_path().serialize(s)
+    }
+}
+#[cfg(feature = "std")]
+impl Deserialize for PathBuf {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        d.deserialize::<String>().map(PathBuf::from)
+    }
+}
+
+impl<T: Serialize> Serialize for Range<T> {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        self.start.serialize(s)?;
+        self.end.serialize(s)
+    }
+}
+impl<T: Deserialize> Deserialize for Range<T> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        Ok(d.deserialize()?..d.deserialize()?)
+    }
+}
+impl<T: FixedSize> FixedSize for Range<T> {
+    const SIZE: usize = 2 * T::SIZE;
+}
+impl<T: Serialize> Serialize for RangeInclusive<T> {
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        self.start().serialize(s)?;
+        self.end().serialize(s)
+    }
+}
+impl<T: Deserialize> Deserialize for RangeInclusive<T> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        Ok(d.deserialize()?..=d.deserialize()?)
+    }
+}
+impl<T: FixedSize> FixedSize for RangeInclusive<T> {
+    const SIZE: usize = 2 * T::SIZE;
+}
+
+impl<T: Serialize, E: Serialize> Serialize for Result<T, E> {
+    fn serialize<Enc: Encoder>(&self, s: &mut Enc) -> BinverseResult<()> {
+        match self {
+            Ok(t) => { 0_u8.serialize(s)?; t.serialize(s) }
+            Err(e) => { 1_u8.serialize(s)?; e.serialize(s) }
+        }
+    }
+}
+impl<T: Deserialize, E: Deserialize> Deserialize for Result<T, E> {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        Ok(match d.deserialize()? {
+            0_u8 => Ok(d.deserialize()?),
+            1_u8 => Err(d.deserialize()?),
+            _ => return Err(BinverseError::InvalidData)
+        })
+    }
+}
@@ -1,53 +1,362 @@
-use std::io::{Read, Write};
+use crate::{error::{BinverseError, BinverseResult}, io::{Read, SliceWriter, Write}, serialize::{Deserialize, Serialize, SizeBytes, SizedDeserialize, SizedSerialize}, varint};
 
-use crate::{error::{BinverseError, BinverseResult}, serialize::{Deserialize, Serialize, SizeBytes, SizedDeserialize, SizedSerialize}, varint};
+/// A position in a serialized stream, measured in bytes from the point where
+/// the [Serializer]/[Deserializer] was created. Returned by
+/// [`Serializer::checkpoint`] and [`Deserializer::checkpoint`] and used to
+/// measure the size of everything written or read after it.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Checkpoint(u64);
+impl Checkpoint {
+    /// The byte offset of the checkpoint.
+    pub fn offset(&self) -> u64 { self.0 }
+
+    fn since(self, position: u64) -> BinverseResult<u64> {
+        position.checked_sub(self.0).ok_or(BinverseError::InvalidCheckpoint { offset: self.0, position })
+    }
+}
+
+macro_rules! encode_numbers {
+    ($($t: ty, $write: ident),*) => {
+        $(
+            #[doc = concat!("Writes a [", stringify!($t), "]. The default implementation writes it in little endian byte order.")]
+            fn $write(&mut self, x: $t) -> BinverseResult<()> {
+                self.write(&x.to_le_bytes())
+            }
+        )*
+    };
+}
+macro_rules! decode_numbers {
+    ($($t: ty, $read: ident),*) => {
+        $(
+            #[doc = concat!("Reads a [", stringify!($t), "]. The default implementation reads it in little endian byte order.")]
+            fn $read(&mut self) -> BinverseResult<$t> {
+                let mut b = [0; core::mem::size_of::<$t>()];
+                self.read(&mut b)?;
+                Ok(<$t>::from_le_bytes(b))
+            }
+        )*
+    };
+}
+
+/// The backend [Serialize] implementations write to. [Serializer] writes the
+/// binverse format to a [Write]r, other encoders can for example count or
+/// hash the bytes or use a different encoding for numbers and lengths.
+///
+/// Only [`Encoder::write`] and [`Encoder::revision`] have to be implemented,
+/// the other methods write the binverse format using them. Numbers have to
+/// keep their size when their encoding is changed, because the sizes of
+/// [FixedSize](crate::serialize::FixedSize) types are used to skip them.
+pub trait Encoder {
+    /// Writes raw bytes. Should only be used when the size will be known
+    /// when decoding.
+    fn write(&mut self, buf: &[u8]) -> BinverseResult<()>;
+
+    /// Returns the revision the data is written for.
+    fn revision(&self) -> u32;
+
+    /// Returns true if equal values have to be written as the same bytes,
+    /// see [`Serializer::canonical`]. Defaults to false.
+    fn is_canonical(&self) -> bool { false }
+
+    encode_numbers!(
+        u8, write_u8, i8, write_i8,
+        u16, write_u16, i16, write_i16,
+        u32, write_u32, i32, write_i32,
+        u64, write_u64, i64, write_i64,
+        u128, write_u128, i128, write_i128,
+        f32, write_f32, f64, write_f64
+    );
+
+    /// Writes a variable sized integer, which is used for [usize], [isize],
+    /// [VarInt](crate::varint::VarInt) and [SizeBytes::Var] lengths.
+    fn write_varint(&mut self, x: u64) -> BinverseResult<()> {
+        let mut buf = [0; varint::MAX_LEN];
+        let mut rest = buf.as_mut_slice();
+        varint::write(x, &mut rest)?;
+        let len = varint::MAX_LEN - rest.len();
+        self.write(&buf[..len])
+    }
+
+    /// Writes the length of a sized data structure. Returns
+    /// [`BinverseError::SizeExceeded`] if the length doesn't fit into the
+    /// size bytes.
+    fn write_size(&mut self, size_bytes: SizeBytes, size: usize) -> BinverseResult<()> {
+        if size as u64 > size_bytes.maximum() {
+            return Err(BinverseError::SizeExceeded { limit: size_bytes, found: size });
+        }
+        match size_bytes {
+            SizeBytes::One   => self.write_u8(size as u8),
+            SizeBytes::Two   => self.write_u16(size as u16),
+            SizeBytes::Four  => self.write_u32(size as u32),
+            SizeBytes::Eight => self.write_u64(size as u64),
+            SizeBytes::Var   => self.write_varint(size as u64)
+        }
+    }
+
+    /// Serializes a sized data structure together with its length, see
+    /// [`Serializer::serialize_sized`].
+    fn serialize_sized<T: SizedSerialize>(&mut self, size_bytes: SizeBytes, t: &T) -> BinverseResult<()>
+    where Self: Sized {
+        let size = t.size();
+        self.write_size(size_bytes, size)?;
+        t.serialize_sized(self, size)
+    }
+}
+
+/// The backend [Deserialize] implementations read from, the counterpart to
+/// [Encoder]. [Deserializer] reads the binverse format from a [Read]er.
+///
+/// Only [`Decoder::read`] and [`Decoder::revision`] have to be implemented,
+/// the other methods read the binverse format using them.
+pub trait Decoder {
+    /// Reads raw bytes into a byte slice. Should only be used when
+    /// implementing new [Deserialize] implementations that can't make use of
+    /// existing implementations.
+    fn read(&mut self, buf: &mut [u8]) -> BinverseResult<()>;
+
+    /// Returns the revision of the data being decoded.
+    fn revision(&self) -> u32;
+
+    /// Returns true if data that isn't canonical has to be rejected, see
+    /// [`Deserializer::strict`]. Defaults to false. The keys of maps and sets
+    /// are compared using the bytes read for them, so strict decoders have
+    /// to keep the default implementations of the `read_*` methods.
+    fn is_strict(&self) -> bool { false }
+
+    /// Reads and discards `n` bytes.
+    fn skip(&mut self, mut n: u64) -> BinverseResult<()> {
+        let mut buf = [0; 256];
+        while n > 0 {
+            let len = n.min(buf.len() as u64) as usize;
+            self.read(&mut buf[..len])?;
+            n -= len as u64;
+        }
+        Ok(())
+    }
+
+    decode_numbers!(
+        u8, read_u8, i8, read_i8,
+        u16, read_u16, i16, read_i16,
+        u32, read_u32, i32, read_i32,
+        u64, read_u64, i64, read_i64,
+        u128, read_u128, i128, read_i128,
+        f32, read_f32, f64, read_f64
+    );
+
+    /// Reads a variable sized integer, see [`Encoder::write_varint`]. In
+    /// strict mode, varints using more bytes than necessary are rejected.
+    fn read_varint(&mut self) -> BinverseResult<u64> {
+        let mut buf = [0; varint::MAX_LEN];
+        let mut len = 0;
+        while len < varint::MAX_LEN {
+            self.read(&mut buf[len..len + 1])?;
+            len += 1;
+            if buf[len - 1] < 0x80 {
+                break;
+            }
+        }
+        let x = varint::read(&buf[..len])?;
+        if self.is_strict() && len > varint::len(x) {
+            return Err(BinverseError::NonCanonical);
+        }
+        Ok(x)
+    }
+
+    /// Reads the length of a sized data structure, see [`Encoder::write_size`].
+    fn read_size(&mut self, size_bytes: SizeBytes) -> BinverseResult<usize> {
+        Ok(match size_bytes {
+            SizeBytes::One   => self.read_u8()? as usize,
+            SizeBytes::Two   => self.read_u16()? as usize,
+            SizeBytes::Four  => self.read_u32()? as usize,
+            SizeBytes::Eight => usize::try_from(self.read_u64()?).map_err(|_| BinverseError::InvalidData)?,
+            SizeBytes::Var   => usize::try_from(self.read_varint()?).map_err(|_| BinverseError::InvalidData)?
+        })
+    }
+
+    /// Deserializes a value, see [`Deserializer::deserialize`].
+    fn deserialize<T: Deserialize>(&mut self) -> BinverseResult<T>
+    where Self: Sized {
+        T::deserialize(self)
+    }
+
+    /// Deserializes a data structure with a size, see [`Deserializer::deserialize_sized`].
+    fn deserialize_sized<T: SizedDeserialize>(&mut self, size_bytes: SizeBytes) -> BinverseResult<T>
+    where Self: Sized {
+        let size = self.read_size(size_bytes)?;
+        T::deserialize_sized(self, size)
+    }
+
+    /// Deserializes into an existing value, see [`Deserialize::deserialize_in_place`].
+    fn deserialize_in_place<T: Deserialize>(&mut self, t: &mut T) -> BinverseResult<()>
+    where Self: Sized {
+        t.deserialize_in_place(self)
+    }
+
+    /// Deserializes a data structure with a size into an existing instance,
+    /// see [`SizedDeserialize::deserialize_sized_in_place`].
+    fn deserialize_sized_in_place<T: SizedDeserialize>(&mut self, t: &mut T, size_bytes: SizeBytes) -> BinverseResult<()>
+    where Self: Sized {
+        let size = self.read_size(size_bytes)?;
+        t.deserialize_sized_in_place(self, size)
+    }
+
+    /// Skips a value of the type, see [`Deserialize::skip`].
+    fn skip_value<T: Deserialize>(&mut self) -> BinverseResult<()>
+    where Self: Sized {
+        T::skip(self)
+    }
+
+    /// Skips a data structure with a size, see [`SizedDeserialize::skip_sized`].
+    fn skip_sized<T: SizedDeserialize>(&mut self, size_bytes: SizeBytes) -> BinverseResult<()>
+    where Self: Sized {
+        let size = self.read_size(size_bytes)?;
+        T::skip_sized(self, size)
+    }
+}
 
 /// A serializer used to write serialized data to the writer.
 pub struct Serializer<W: Write> {
-    /// The underlying writer.
+    /// The underlying writer. Bytes written to it directly are not included
+    /// in [`Serializer::bytes_written`]. In buffered mode, call
+    /// [`Serializer::flush`] before writing to it directly.
     pub w: W,
+    revision: u32,
+    written: u64,
+    limit: Option<u64>,
+    canonical: bool,
+    #[cfg(feature = "alloc")]
+    buf: Option<alloc::vec::Vec<u8>>
 }
 impl<W: Write> Serializer<W> {
     /// Creates a new serializer. The revision will be written to the data to
     /// make it possible to parse the data in future revisions. If the revision
     ///  should not be written, use [`Serializer::new_no_revision`].
     pub fn new(w: W, revision: u32) -> BinverseResult<Self> {
-        let mut s = Self { w };
+        let mut s = Self::new_no_revision(w).for_revision(revision);
         revision.serialize(&mut s)?;
         Ok(s)
     }
 
     /// Create a new Serializer, but without writing the revision into the stream.
+    /// The data is written for the latest revision, use
+    /// [`Serializer::for_revision`] to write data for an older one.
     pub fn new_no_revision(w: W) -> Self {
-        Self { w }
+        Self {
+            w,
+            revision: u32::MAX,
+            written: 0,
+            limit: None,
+            canonical: false,
+            #[cfg(feature = "alloc")]
+            buf: None
+        }
+    }
+
+    /// Sets the revision the data is written for without writing it into the
+    /// stream. Fields that were added after the revision are omitted by
+    /// derived implementations, so data for readers of older revisions can
+    /// be produced.
+    pub fn for_revision(mut self, revision: u32) -> Self {
+        self.revision = revision;
+        self
+    }
+
+    /// Returns the revision the data is written for. This is [u32::MAX] for
+    /// serializers created with [`Serializer::new_no_revision`], unless
+    /// [`Serializer::for_revision`] was used.
+    pub fn revision(&self) -> u32 { self.revision }
+
+    /// Enables the canonical mode. In canonical mode, equal values always
+    /// serialize to the same bytes, which makes the output suitable for
+    /// hashing and signing:
+    /// - Entries of maps and sets are written in the order of their
+    ///   serialized keys instead of their iteration order.
+    /// - All NaN floats are written as the same quiet NaN.
+    ///
+    /// The output is still readable by any [Deserializer], use
+    /// [`Deserializer::strict`] to reject data that isn't canonical.
+    pub fn canonical(mut self) -> Self {
+        self.canonical = true;
+        self
+    }
+
+    /// Returns true if the serializer is in canonical mode, see [`Serializer::canonical`].
+    pub fn is_canonical(&self) -> bool { self.canonical }
+
+    /// Enables the buffered mode. Small writes are collected in a buffer of
+    /// `capacity` bytes and passed to the writer in larger chunks, so
+    /// wrapping a file or socket in a `BufWriter` isn't needed. Buffered data
+    /// is written by [`Serializer::flush`] and [`Serializer::finish`] and is
+    /// lost if the serializer is dropped without calling them. If writing the
+    /// buffer to the writer fails, the buffered data is discarded instead of
+    /// being written again later, because the writer may have accepted a part
+    /// of it.
+    #[cfg(feature = "alloc")]
+    pub fn buffered(mut self, capacity: usize) -> Self {
+        self.buf = Some(alloc::vec::Vec::with_capacity(capacity));
+        self
     }
 
+
     /// Write a raw byte buffer into the output. Should only be used when the
     /// size will be known when deserializing, use the [SizedSerialize]/[SizedDeserialize]
     /// implementations for `[u8]` or `Vec<u8>` otherwise.
     pub fn write(&mut self, buf: &[u8]) -> BinverseResult<()> {
+        if let Some(limit) = self.limit {
+            let needed = self.written + buf.len() as u64;
+            if needed > limit {
+                return Err(BinverseError::BufferFull { needed });
+            }
+        }
+        #[cfg(feature = "alloc")]
+        if let Some(b) = &mut self.buf {
+            if b.len() + buf.len() > b.capacity() {
+                // the buffer is cleared even if writing fails, the writer
+                // may have accepted a part of it already
+                let result = self.w.write_all(b);
+                b.clear();
+                result?;
+            }
+            if buf.len() >= b.capacity() {
+                self.w.write_all(buf)?;
+            } else {
+                b.extend_from_slice(buf);
+            }
+            self.written += buf.len() as u64;
+            return Ok(());
+        }
         self.w.write_all(buf)?;
+        self.written += buf.len() as u64;
         Ok(())
     }
 
-    pub(crate) fn write_size(&mut self, sb: SizeBytes, size: usize) -> BinverseResult<()> {
-        use SizeBytes::*;
-        let max_size = match sb {
-            One         =>  u8::MAX as usize,
-            Two         => u16::MAX as usize,
-            Four        => u32::MAX as usize,
-            Eight | Var => u64::MAX as usize,   
-        };
-        if size > max_size {
-            return Err(BinverseError::SizeExceeded { limit: sb, found: size });
-        }
-        match sb {
-            SizeBytes::One   => (size as  u8).serialize(self),
-            SizeBytes::Two   => (size as u16).serialize(self),
-            SizeBytes::Four  => (size as u32).serialize(self),
-            SizeBytes::Eight => (size as u64).serialize(self),
-            SizeBytes::Var   => varint::write(size as u64, &mut self.w)
+    /// Writes all buffered data and flushes the writer.
+    pub fn flush(&mut self) -> BinverseResult<()> {
+        #[cfg(feature = "alloc")]
+        if let Some(b) = &mut self.buf {
+            let result = self.w.write_all(b);
+            b.clear();
+            result?;
         }
+        self.w.flush()?;
+        Ok(())
+    }
+
+    /// Returns the number of bytes written since the serializer was created,
+    /// including the revision.
+    pub fn bytes_written(&self) -> u64 { self.written }
+
+    /// Marks the current position in the output. Use [`Serializer::since`]
+    /// to get the number of bytes written after the checkpoint, for example
+    /// to measure the size of a sub-object.
+    pub fn checkpoint(&self) -> Checkpoint { Checkpoint(self.written) }
+
+    /// Returns the number of bytes written since the checkpoint was created.
+    /// Returns [`BinverseError::InvalidCheckpoint`] if the checkpoint is
+    /// ahead of the current position, which means it was created by another
+    /// serializer.
+    pub fn since(&self, checkpoint: Checkpoint) -> BinverseResult<u64> {
+        checkpoint.since(self.written)
     }
 
     /// Serialize a sized data structure. Use the `size_bytes` parameter to
@@ -59,16 +368,125 @@ impl<W: Write> Serializer<W> {
         self.write_size(size_bytes, size)?;
         t.serialize_sized(self, size)
     }
-    /// Returns the inner writer.
-    pub fn finish(self) -> W { self.w }
+    /// Flushes the serializer (see [`Serializer::flush`]) and returns the
+    /// inner writer.
+    pub fn finish(mut self) -> BinverseResult<W> {
+        self.flush()?;
+        Ok(self.w)
+    }
+}
+
+impl<W: Write> Encoder for Serializer<W> {
+    fn write(&mut self, buf: &[u8]) -> BinverseResult<()> { Serializer::write(self, buf) }
+    fn revision(&self) -> u32 { self.revision }
+    fn is_canonical(&self) -> bool { self.canonical }
+}
+
+/// Creates a [Serializer] for a different writer with the revision and
+/// canonical mode of an encoder. Used to serialize parts of the data
+/// separately, for example to sort them before they are written.
+#[cfg(feature = "alloc")]
+pub(crate) fn sub_serializer<V: Write>(e: &impl Encoder, w: V) -> Serializer<V> {
+    Serializer { revision: e.revision(), canonical: e.is_canonical(), ..Serializer::new_no_revision(w) }
+}
+
+/// An encoder that passes the canonical encoding to a [Hasher](core::hash::Hasher)
+/// using [`Hasher::write`](core::hash::Hasher::write) only, so the bytes
+/// hashed don't depend on the platform. Used by [crate::hash].
+pub(crate) struct HashEncoder<'a, H> {
+    pub(crate) hasher: &'a mut H
+}
+impl<H: core::hash::Hasher> Encoder for HashEncoder<'_, H> {
+    fn write(&mut self, buf: &[u8]) -> BinverseResult<()> {
+        self.hasher.write(buf);
+        Ok(())
+    }
+    fn revision(&self) -> u32 { u32::MAX }
+    fn is_canonical(&self) -> bool { true }
+}
+
+impl<'a> Serializer<SliceWriter<'a>> {
+    /// Creates a serializer that writes into a fixed size buffer without
+    /// allocating. The revision is written like in [`Serializer::new`].
+    /// When the buffer is full, [`BinverseError::BufferFull`] is returned.
+    /// Use [`crate::serialized_size`] to compute the required buffer size
+    /// beforehand. The number of bytes used can be retrieved with
+    /// [`Serializer::bytes_written`] or [`SliceWriter::len`] after calling
+    /// [`Serializer::finish`].
+    pub fn new_slice(buf: &'a mut [u8], revision: u32) -> BinverseResult<Self> {
+        let mut s = Self::new_slice_no_revision(buf).for_revision(revision);
+        revision.serialize(&mut s)?;
+        Ok(s)
+    }
+
+    /// Creates a serializer that writes into a fixed size buffer, but without
+    /// writing the revision. See [`Serializer::new_slice`].
+    pub fn new_slice_no_revision(buf: &'a mut [u8]) -> Self {
+        let limit = buf.len() as u64;
+        Self { limit: Some(limit), ..Self::new_no_revision(SliceWriter::new(buf)) }
+    }
 }
 
 /// Reads previously serialized data from a reader. Note that all calls must be
 /// the opposite from the calls used when serializing so the data matches.
 pub struct Deserializer<R: Read> {
-    /// The underlying reader. 
+    /// The underlying reader. Bytes read from it directly are not included
+    /// in [`Deserializer::bytes_read`]. In buffered mode, the reader may
+    /// already be ahead of the deserialized data.
     pub r: R,
-    revision: u32
+    revision: u32,
+    read: u64,
+    strict: bool,
+    #[cfg(feature = "std")]
+    ahead: Option<ReadAhead>,
+    #[cfg(feature = "std")]
+    seek: Option<fn(&mut R, u64) -> std::io::Result<()>>
+}
+
+/// The read-ahead buffer of a buffered [Deserializer]. `buf[pos..end]`
+/// contains the bytes that were read from the reader but not yet consumed.
+#[cfg(feature = "std")]
+struct ReadAhead {
+    buf: std::boxed::Box<[u8]>,
+    pos: usize,
+    end: usize
+}
+#[cfg(feature = "std")]
+impl ReadAhead {
+    fn read<R: Read>(&mut self, r: &mut R, out: &mut [u8]) -> BinverseResult<()> {
+        let mut filled = 0;
+        while filled < out.len() {
+            if self.pos == self.end {
+                // large reads bypass the buffer
+                if out.len() - filled >= self.buf.len() {
+                    r.read_exact(&mut out[filled..])?;
+                    return Ok(());
+                }
+                self.fill(r)?;
+            }
+            let n = (self.end - self.pos).min(out.len() - filled);
+            out[filled..filled + n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+            self.pos += n;
+            filled += n;
+        }
+        Ok(())
+    }
+
+    fn fill<R: Read>(&mut self, r: &mut R) -> BinverseResult<()> {
+        let n = loop {
+            match r.read(&mut self.buf) {
+                Ok(n) => break n,
+                Err(err) if err.kind() == crate::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into())
+            }
+        };
+        if n == 0 {
+            return Err(crate::io::Error::from(crate::io::ErrorKind::UnexpectedEof).into());
+        }
+        self.pos = 0;
+        self.end = n;
+        Ok(())
+    }
 }
 
 impl<R: Read> Deserializer<R> {
@@ -76,10 +494,7 @@ impl<R: Read> Deserializer<R> {
     /// is read from the reader. If the revision should not be read, use
     /// [`Deserializer::new_no_revision`].
     pub fn new(r: R) -> BinverseResult<Self> {
-        let mut d = Self {
-            r,
-            revision: 0
-        };
+        let mut d = Self::new_no_revision(r, 0);
         d.revision = d.deserialize()?;
         Ok(d)
     }
@@ -88,25 +503,127 @@ impl<R: Read> Deserializer<R> {
     /// Instead, the revision has to be passed. Providing data created in a different
     /// revision than specified can lead to invalid data or errors.
     pub fn new_no_revision(r: R, revision: u32) -> Self {
-        Self { r, revision }
+        Self {
+            r,
+            revision,
+            read: 0,
+            strict: false,
+            #[cfg(feature = "std")]
+            ahead: None,
+            #[cfg(feature = "std")]
+            seek: None
+        }
+    }
+
+    /// Enables the strict mode. In strict mode, data that wasn't written in
+    /// the canonical mode of the [Serializer] (see [`Serializer::canonical`])
+    /// is rejected with [`BinverseError::NonCanonical`]. This includes
+    /// varints that use more bytes than necessary, unsorted or duplicate
+    /// map and set entries and NaN floats other than the canonical NaN.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
     }
-    
+
+    /// Returns true if the deserializer is in strict mode, see [`Deserializer::strict`].
+    pub fn is_strict(&self) -> bool { self.strict }
+
+    /// Enables the buffered mode. The reader is read in chunks of up to
+    /// `capacity` bytes, so wrapping a file or socket in a `BufReader` isn't
+    /// needed. Bytes that were read ahead but not deserialized yet are
+    /// available through [`Deserializer::buffered_bytes`].
+    #[cfg(feature = "std")]
+    pub fn buffered(mut self, capacity: usize) -> Self {
+        self.ahead = Some(ReadAhead { buf: std::vec![0; capacity].into_boxed_slice(), pos: 0, end: 0 });
+        self
+    }
+
+    /// Returns the bytes that were read from the reader in buffered mode but
+    /// not deserialized yet.
+    #[cfg(feature = "std")]
+    pub fn buffered_bytes(&self) -> &[u8] {
+        self.ahead.as_ref().map_or(&[], |a| &a.buf[a.pos..a.end])
+    }
+
     /// Reads raw bytes into a byte slice. Should only be used when
     /// implementing new [Deserialize] implementations that can't make use of
     /// existing implementations.
     pub fn read(&mut self, buf: &mut [u8]) -> BinverseResult<()> {
+        #[cfg(feature = "std")]
+        match &mut self.ahead {
+            Some(ahead) => ahead.read(&mut self.r, buf)?,
+            None => self.r.read_exact(buf)?
+        }
+        #[cfg(not(feature = "std"))]
         self.r.read_exact(buf)?;
+        self.read += buf.len() as u64;
         Ok(())
     }
 
-    pub(crate) fn read_size(&mut self, sb: SizeBytes) -> BinverseResult<usize>  {
-        Ok(match sb {
-            SizeBytes::One   => self.deserialize::< u8>()? as usize,
-            SizeBytes::Two   => self.deserialize::<u16>()? as usize,
-            SizeBytes::Four  => self.deserialize::<u32>()? as usize,
-            SizeBytes::Eight => self.deserialize::<u64>()? as usize,
-            SizeBytes::Var   => varint::read(&mut self.r)? as usize
-        })
+    /// Returns the number of bytes read since the deserializer was created,
+    /// including the revision.
+    pub fn bytes_read(&self) -> u64 { self.read }
+
+    /// Marks the current position in the input. Use [`Deserializer::since`],
+    /// [`Deserializer::verify_len`] or [`Deserializer::skip_to`] to work with
+    /// data of a declared length starting at the checkpoint.
+    pub fn checkpoint(&self) -> Checkpoint { Checkpoint(self.read) }
+
+    /// Returns the number of bytes read since the checkpoint was created.
+    /// Returns [`BinverseError::InvalidCheckpoint`] if the checkpoint is
+    /// ahead of the current position, which means it was created by another
+    /// deserializer.
+    pub fn since(&self, checkpoint: Checkpoint) -> BinverseResult<u64> {
+        checkpoint.since(self.read)
+    }
+
+    /// Reads and discards `n` bytes. In seekable mode (see
+    /// [`Deserializer::seekable`]), the reader seeks over the bytes instead.
+    pub fn skip(&mut self, mut n: u64) -> BinverseResult<()> {
+        #[cfg(feature = "std")]
+        if let Some(seek) = self.seek {
+            if let Some(ahead) = &mut self.ahead {
+                let buffered = n.min((ahead.end - ahead.pos) as u64);
+                ahead.pos += buffered as usize;
+                self.read += buffered;
+                n -= buffered;
+            }
+            if n > 0 {
+                seek(&mut self.r, n)?;
+                self.read += n;
+            }
+            return Ok(());
+        }
+        let mut buf = [0; 256];
+        while n > 0 {
+            let len = n.min(buf.len() as u64) as usize;
+            self.read(&mut buf[..len])?;
+            n -= len as u64;
+        }
+        Ok(())
+    }
+
+    /// Checks that exactly `len` bytes were read since the checkpoint.
+    /// Returns [`BinverseError::LengthMismatch`] otherwise.
+    pub fn verify_len(&self, checkpoint: Checkpoint, len: u64) -> BinverseResult<()> {
+        let found = self.since(checkpoint)?;
+        if found != len {
+            return Err(BinverseError::LengthMismatch { expected: len, found });
+        }
+        Ok(())
+    }
+
+    /// Skips the remaining bytes of data with a declared length of `len`
+    /// bytes that started at the checkpoint. This can be used to skip
+    /// trailing data that is unknown to the current version. Returns
+    /// [`BinverseError::LengthMismatch`] if more than `len` bytes were
+    /// already read.
+    pub fn skip_to(&mut self, checkpoint: Checkpoint, len: u64) -> BinverseResult<()> {
+        let found = self.since(checkpoint)?;
+        if found > len {
+            return Err(BinverseError::LengthMismatch { expected: len, found });
+        }
+        self.skip(len - found)
     }
 
     /// Deserializes something. The type has to be known and has to match the
@@ -119,9 +636,55 @@ impl<R: Read> Deserializer<R> {
         let size = self.read_size(size_bytes)?;
         T::deserialize_sized(self, size)
     }
+    /// Deserializes into an existing value, see [`Deserialize::deserialize_in_place`].
+    pub fn deserialize_in_place<T: Deserialize>(&mut self, t: &mut T) -> BinverseResult<()> {
+        t.deserialize_in_place(self)
+    }
+
+    /// Deserializes a data structure with a size into an existing instance,
+    /// see [`SizedDeserialize::deserialize_sized_in_place`].
+    pub fn deserialize_sized_in_place<T: SizedDeserialize>(&mut self, t: &mut T, size_bytes: SizeBytes) -> BinverseResult<()> {
+        let size = self.read_size(size_bytes)?;
+        t.deserialize_sized_in_place(self, size)
+    }
+
+    /// Skips a value of the type, see [`Deserialize::skip`].
+    pub fn skip_value<T: Deserialize>(&mut self) -> BinverseResult<()> { T::skip(self) }
+
+    /// Skips a data structure with a size, see [`SizedDeserialize::skip_sized`].
+    /// Type and size_bytes have to match the serialized data structure.
+    pub fn skip_sized<T: SizedDeserialize>(&mut self, size_bytes: SizeBytes) -> BinverseResult<()> {
+        let size = self.read_size(size_bytes)?;
+        T::skip_sized(self, size)
+    }
+
     /// Get the revision of the data currently being deserialized. Used when
     /// reading version-dependent data. 
     pub fn revision(&self) -> u32 { self.revision }
-    /// Returns the inner reader.
+    /// Returns the inner reader. In buffered mode, bytes that were read ahead
+    /// are discarded, use [`Deserializer::buffered_bytes`] to retrieve them
+    /// first.
     pub fn finish(self) -> R { self.r }
 }
+
+impl<R: Read> Decoder for Deserializer<R> {
+    fn read(&mut self, buf: &mut [u8]) -> BinverseResult<()> { Deserializer::read(self, buf) }
+    fn revision(&self) -> u32 { self.revision }
+    fn is_strict(&self) -> bool { self.strict }
+    fn skip(&mut self, n: u64) -> BinverseResult<()> { Deserializer::skip(self, n) }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read + std::io::Seek> Deserializer<R> {
+    /// Enables the seekable mode. Skipped data (see [`Deserializer::skip`]
+    /// and [`Deserialize::skip`]) is passed over by seeking the reader
+    /// instead of reading it, which is faster for large values in files.
+    /// Seeking past the end of the reader is only detected by the next read.
+    pub fn seekable(mut self) -> Self {
+        self.seek = Some(|r, n| {
+            let n = i64::try_from(n).map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidInput))?;
+            r.seek(std::io::SeekFrom::Current(n)).map(drop)
+        });
+        self
+    }
+}
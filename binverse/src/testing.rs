@@ -0,0 +1,140 @@
+use std::fmt::Debug;
+
+use proptest::{arbitrary::{any, Arbitrary}, collection, prop_oneof, strategy::{BoxedStrategy, Just, Strategy}};
+
+use crate::{error::{BinverseError, BinverseResult}, serialize::{Deserialize, Serialize, SizeBytes, SizedDeserialize, SizedSerialize}, streams::{Deserializer, Serializer}, varint::VarInt};
+
+/// Checks that all bytes were read by a deserializer over a byte slice.
+fn check_leftover(d: Deserializer<&[u8]>) -> BinverseResult<()> {
+    let read = d.bytes_read();
+    let leftover = d.finish().len() as u64;
+    if leftover != 0 {
+        return Err(BinverseError::LengthMismatch { expected: read + leftover, found: read });
+    }
+    Ok(())
+}
+
+/// Serializes a value with the revision and deserializes it again. Returns
+/// [`BinverseError::LengthMismatch`] if the deserialization didn't read all bytes.
+pub fn roundtrip<T: Serialize + Deserialize>(val: &T, revision: u32) -> BinverseResult<T> {
+    let mut s = Serializer::new(Vec::new(), revision)?;
+    val.serialize(&mut s)?;
+    let buf = s.finish()?;
+    let mut d = Deserializer::new(buf.as_slice())?;
+    let t = d.deserialize()?;
+    check_leftover(d)?;
+    Ok(t)
+}
+
+/// Like [roundtrip], but serializes the value as a sized data structure
+/// using the size bytes.
+pub fn roundtrip_sized<T: SizedSerialize + SizedDeserialize>(val: &T, sb: SizeBytes, revision: u32) -> BinverseResult<T> {
+    let mut s = Serializer::new(Vec::new(), revision)?;
+    s.serialize_sized(sb, val)?;
+    let buf = s.finish()?;
+    let mut d = Deserializer::new(buf.as_slice())?;
+    let t = d.deserialize_sized(sb)?;
+    check_leftover(d)?;
+    Ok(t)
+}
+
+/// Serializes `old` in revision `old_revision` and deserializes the data as a
+/// different type, usually the same `#[serializable]` struct in a later
+/// revision. Returns [`BinverseError::LengthMismatch`] if the deserialization
+/// didn't read all bytes.
+pub fn cross_revision<A: Serialize, B: Deserialize>(old: &A, old_revision: u32) -> BinverseResult<B> {
+    let mut s = Serializer::new(Vec::new(), old_revision)?;
+    old.serialize(&mut s)?;
+    let buf = s.finish()?;
+    let mut d = Deserializer::new(buf.as_slice())?;
+    let b = d.deserialize()?;
+    check_leftover(d)?;
+    Ok(b)
+}
+
+/// Asserts that a value is equal to itself after being serialized and
+/// deserialized and that no bytes are left over. The latest revision
+/// (`u32::MAX`) is used so all `Added` fields of versioned types are
+/// included, see [assert_roundtrip_at] to check a specific revision.
+#[track_caller]
+pub fn assert_roundtrip<T: Serialize + Deserialize + PartialEq + Debug>(val: &T) {
+    assert_roundtrip_at(val, u32::MAX)
+}
+
+/// Like [assert_roundtrip], but uses the revision for serializing and
+/// deserializing.
+#[track_caller]
+pub fn assert_roundtrip_at<T: Serialize + Deserialize + PartialEq + Debug>(val: &T, revision: u32) {
+    match roundtrip(val, revision) {
+        Ok(new_val) => assert_eq!(val, &new_val, "{}", std::any::type_name::<T>()),
+        Err(err) => panic!("Round trip of {} failed: {}", std::any::type_name::<T>(), err)
+    }
+}
+
+/// Like [assert_roundtrip], but serializes the value as a sized data
+/// structure using the size bytes.
+#[track_caller]
+pub fn assert_roundtrip_sized<T: SizedSerialize + SizedDeserialize + PartialEq + Debug>(val: &T, sb: SizeBytes) {
+    match roundtrip_sized(val, sb, u32::MAX) {
+        Ok(new_val) => assert_eq!(val, &new_val, "{}", std::any::type_name::<T>()),
+        Err(err) => panic!("Sized round trip of {} failed: {}", std::any::type_name::<T>(), err)
+    }
+}
+
+/// Asserts that `old`, serialized in revision `old_revision`, deserializes
+/// to `expected` and that no bytes are left over. See [cross_revision].
+#[track_caller]
+pub fn assert_cross_revision<A: Serialize, B: Deserialize + PartialEq + Debug>(old: &A, old_revision: u32, expected: &B) {
+    match cross_revision::<A, B>(old, old_revision) {
+        Ok(new_val) => assert_eq!(expected, &new_val, "{} from revision {}", std::any::type_name::<B>(), old_revision),
+        Err(err) => panic!("Reading {} from revision {} failed: {}", std::any::type_name::<B>(), old_revision, err)
+    }
+}
+
+impl Arbitrary for VarInt {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Generates values of all encoded lengths, including the boundaries
+    /// where the length changes.
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            any::<u64>(),
+            (0..64_u32).prop_map(|bits| 1_u64 << bits),
+            (1..64_u32).prop_map(|bits| (1_u64 << bits) - 1),
+        ].prop_map(VarInt).boxed()
+    }
+}
+
+impl Arbitrary for SizeBytes {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            Just(SizeBytes::One),
+            Just(SizeBytes::Two),
+            Just(SizeBytes::Four),
+            Just(SizeBytes::Eight),
+            Just(SizeBytes::Var),
+        ].boxed()
+    }
+}
+
+/// Generates vecs with a length that fits the size bytes. `max_len` limits the
+/// length for size bytes with a large maximum.
+pub fn vec_for<S: Strategy>(element: S, sb: SizeBytes, max_len: usize) -> impl Strategy<Value = Vec<S::Value>> {
+    collection::vec(element, 0..=max_len.min(sb.maximum() as usize))
+}
+
+/// Generates strings with a length in bytes that fits the size bytes.
+/// `max_len` limits the length for size bytes with a large maximum.
+pub fn string_for(sb: SizeBytes, max_len: usize) -> impl Strategy<Value = String> {
+    let max_len = max_len.min(sb.maximum() as usize);
+    any::<String>().prop_map(move |mut s| {
+        while s.len() > max_len {
+            s.pop();
+        }
+        s
+    })
+}
@@ -0,0 +1,239 @@
+use std::{fs::{self, File, OpenOptions}, io::{self, Seek, SeekFrom}, marker::PhantomData, path::{Path, PathBuf}, sync::atomic::{AtomicU64, Ordering}};
+
+use crate::{error::{BinverseError, BinverseResult}, serialize::{Deserialize, Serialize}, streams::{Deserializer, Serializer}};
+
+const BUFFER_CAPACITY: usize = 8192;
+const TEMP_EXTENSION: &str = "upgrade-tmp";
+
+/// Makes the names of temporary files unique within the process.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The result of upgrading a single file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upgrade {
+    /// The file was rewritten at the current revision.
+    Upgraded {
+        /// The revision the file was stored in before.
+        from: u32
+    },
+    /// The file already was at the current revision and wasn't touched.
+    Current,
+    /// The file was written in a revision newer than the current one and
+    /// wasn't touched.
+    Newer {
+        /// The revision the file is stored in.
+        revision: u32
+    }
+}
+
+/// The result of upgrading one file in [Upgrader::upgrade_dir].
+#[derive(Debug)]
+pub struct FileResult {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The outcome of the upgrade or the reason it failed.
+    pub result: BinverseResult<Upgrade>
+}
+
+/// Rewrites files containing a single root object of type `T`, written by
+/// [write()](crate::write), at the current revision so later loads don't
+/// have to handle the old revisions anymore.
+///
+/// The upgraded data is written to a temporary file with a unique name in the
+/// same directory which then replaces the original file using a rename, so
+/// the file always contains either the old or the new data, even if the
+/// process is killed while upgrading. The directory is synced after the
+/// rename so the replacement also survives a crash of the system.
+pub struct Upgrader<T> {
+    revision: u32,
+    backup: Option<String>,
+    extension: Option<String>,
+    _marker: PhantomData<fn() -> T>
+}
+impl<T: Serialize + Deserialize> Upgrader<T> {
+    /// Creates an upgrader writing the files at `revision`. The old data is
+    /// read using [Deserialize::deserialize_root] like in [read()](crate::read),
+    /// so types with [migrations](crate::migrate) are converted from the
+    /// version used in the revision of the file.
+    pub fn new(revision: u32) -> Self {
+        Self { revision, backup: None, extension: None, _marker: PhantomData }
+    }
+
+    /// Keeps a copy of every upgraded file with the extension appended to
+    /// its file name, for example `save.dat.bak` for the extension `"bak"`.
+    /// Existing backups are overwritten.
+    ///
+    /// The backup is copied from the opened original file, not from its path,
+    /// and replaces the old backup using a rename. If another upgrade replaces
+    /// the file in the meantime, the backup still contains the original data
+    /// and never an upgraded or partially written file.
+    pub fn backup(mut self, extension: &str) -> Self {
+        self.backup = Some(extension.to_owned());
+        self
+    }
+
+    /// Only upgrades files with the extension in [Upgrader::upgrade_dir].
+    pub fn extension(mut self, extension: &str) -> Self {
+        self.extension = Some(extension.to_owned());
+        self
+    }
+
+    /// The revision files are upgraded to.
+    pub fn revision(&self) -> u32 { self.revision }
+
+    /// Upgrades a single file. The file isn't changed if it already is at the
+    /// current revision or if reading it fails.
+    pub fn upgrade_file(&self, path: impl AsRef<Path>) -> BinverseResult<Upgrade> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+        let mut original = file.try_clone()?;
+        let mut d = Deserializer::new(file)?.buffered(BUFFER_CAPACITY);
+        let from = d.revision();
+        if from == self.revision {
+            return Ok(Upgrade::Current);
+        }
+        if from > self.revision {
+            return Ok(Upgrade::Newer { revision: from });
+        }
+        let object = T::deserialize_root(&mut d)?;
+        if d.bytes_read() != metadata.len() {
+            return Err(BinverseError::LengthMismatch { expected: metadata.len(), found: d.bytes_read() });
+        }
+        drop(d);
+
+        if let Some(backup) = &self.backup {
+            original.seek(SeekFrom::Start(0))?;
+            write_backup(&mut original, &append_extension(path, backup), metadata.permissions())?;
+        }
+        drop(original);
+
+        let (temp, file) = create_temp(path)?;
+        if let Err(err) = self.write_temp(file, &object, metadata.permissions()) {
+            let _ = fs::remove_file(&temp);
+            return Err(err);
+        }
+        if let Err(err) = fs::rename(&temp, path) {
+            let _ = fs::remove_file(&temp);
+            return Err(err.into());
+        }
+        sync_parent(path)?;
+        Ok(Upgrade::Upgraded { from })
+    }
+
+    fn write_temp(&self, file: File, object: &T, permissions: fs::Permissions) -> BinverseResult<()> {
+        let mut s = Serializer::new_no_revision(file).buffered(BUFFER_CAPACITY).for_revision(self.revision);
+        self.revision.serialize(&mut s)?;
+        object.serialize(&mut s)?;
+        let file = s.finish()?;
+        file.sync_all()?;
+        file.set_permissions(permissions)?;
+        Ok(())
+    }
+
+    /// Upgrades all files in a directory and its subdirectories, sorted by
+    /// path. Temporary files of the upgrader and backups are skipped.
+    /// Failures don't stop the upgrade of the other files, they are returned
+    /// as results of the files, or of the directories that couldn't be read.
+    /// Only fails if `dir` itself can't be read.
+    pub fn upgrade_dir(&self, dir: impl AsRef<Path>) -> io::Result<Vec<FileResult>> {
+        let mut results = Vec::new();
+        let mut files = Vec::new();
+        list_files(dir.as_ref(), &mut files)?;
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (path, err) in files {
+            let result = match err {
+                Some(err) => Err(err.into()),
+                None if self.should_upgrade(&path) => self.upgrade_file(&path),
+                None => continue
+            };
+            results.push(FileResult { path, result });
+        }
+        Ok(results)
+    }
+
+    fn should_upgrade(&self, path: &Path) -> bool {
+        let extension = path.extension().and_then(|e| e.to_str());
+        if extension == Some(TEMP_EXTENSION) || (extension.is_some() && extension == self.backup.as_deref()) {
+            return false;
+        }
+        self.extension.is_none() || extension == self.extension.as_deref()
+    }
+}
+
+/// Creates a new temporary file next to `path`. The file is opened
+/// exclusively, so concurrent upgrades never write to the same file.
+fn create_temp(path: &Path) -> io::Result<(PathBuf, File)> {
+    loop {
+        let n = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let temp = append_extension(path, &format!("{}-{}.{}", std::process::id(), n, TEMP_EXTENSION));
+        match OpenOptions::new().write(true).create_new(true).open(&temp) {
+            Ok(file) => return Ok((temp, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err)
+        }
+    }
+}
+
+/// Copies the original data to a temporary file and renames it to `backup`,
+/// so the backup is replaced atomically.
+fn write_backup(original: &mut File, backup: &Path, permissions: fs::Permissions) -> io::Result<()> {
+    let (temp, mut file) = create_temp(backup)?;
+    let result = io::copy(original, &mut file)
+        .and_then(|_| file.sync_all())
+        .and_then(|()| file.set_permissions(permissions))
+        .and_then(|()| fs::rename(&temp, backup));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Syncs the directory containing `path` to make a rename durable. Only
+/// supported on Unix, other platforms can't open directories for syncing.
+fn sync_parent(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new(".")
+        };
+        File::open(parent)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    path.into()
+}
+
+/// Collects the files in a directory tree together with the errors of
+/// entries that couldn't be read.
+fn list_files(dir: &Path, files: &mut Vec<(PathBuf, Option<io::Error>)>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                files.push((dir.to_owned(), Some(err)));
+                continue;
+            }
+        };
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(ty) if ty.is_dir() => {
+                if let Err(err) = list_files(&path, files) {
+                    files.push((path, Some(err)));
+                }
+            }
+            Ok(ty) if ty.is_file() => files.push((path, None)),
+            Ok(_) => {}
+            Err(err) => files.push((path, Some(err)))
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,329 @@
+//! A dynamic [Value] tree for reading binverse data without the Rust types,
+//! for example in tooling and migration scripts. Since the binary format
+//! doesn't describe itself, the data is decoded against a [Type] that
+//! matches the encodings of the corresponding Rust types. Encoding a decoded
+//! value with the same type produces exactly the same bytes.
+//!
+//! ```
+//! use binverse::{serialize::SizeBytes, streams::{Deserializer, Serializer}, value::{Field, Type, Value}};
+//!
+//! let ty = Type::Struct(vec![
+//!     Field::new("id", Type::U32),
+//!     Field::new("name", Type::String(SizeBytes::One)),
+//!     Field::new("tags", Type::List(Box::new(Type::VarInt), SizeBytes::Var)).added(2),
+//! ]);
+//! let data = [1, 0, 0, 0, 2, b'h', b'i'];
+//!
+//! // the tags field doesn't exist in revision 1
+//! let mut d = Deserializer::new_no_revision(data.as_slice(), 1);
+//! let value = Value::decode(&mut d, &ty).unwrap();
+//! assert_eq!(value.field("name"), Some(&Value::String("hi".into())));
+//!
+//! let mut s = Serializer::new_no_revision(Vec::new());
+//! value.encode(&mut s, &ty).unwrap();
+//! assert_eq!(s.finish().unwrap(), data);
+//! ```
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use crate::{error::{BinverseError, BinverseResult}, primitives::read_bytes, serialize::{Deserialize, Serialize, SizeBytes}, streams::{Decoder, Encoder}, varint};
+
+/// A runtime description of a serialized type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    /// A [bool].
+    Bool,
+    /// A [u8].
+    U8,
+    /// A [u16].
+    U16,
+    /// A [u32].
+    U32,
+    /// A [u64].
+    U64,
+    /// A [u128].
+    U128,
+    /// An [i8].
+    I8,
+    /// An [i16].
+    I16,
+    /// An [i32].
+    I32,
+    /// An [i64].
+    I64,
+    /// An [i128].
+    I128,
+    /// A [VarInt](crate::varint::VarInt) or a [usize].
+    VarInt,
+    /// An [isize], a zigzag encoded varint.
+    Isize,
+    /// An [f32].
+    F32,
+    /// An [f64].
+    F64,
+    /// A [char].
+    Char,
+    /// A [String] with a length prefix using the SizeBytes.
+    String(SizeBytes),
+    /// A `Vec<u8>` or another byte collection with a length prefix using the
+    /// SizeBytes.
+    Bytes(SizeBytes),
+    /// An array with a fixed number of elements and no length prefix.
+    Array(Box<Type>, usize),
+    /// A [Vec], set or other collection with a length prefix using the
+    /// SizeBytes.
+    List(Box<Type>, SizeBytes),
+    /// A map with a length prefix using the SizeBytes, followed by the keys
+    /// and values.
+    Map(Box<Type>, Box<Type>, SizeBytes),
+    /// An [Option].
+    Option(Box<Type>),
+    /// A tuple. The empty tuple is used for unit types.
+    Tuple(Vec<Type>),
+    /// A `#[serializable]` struct.
+    Struct(Vec<Field>),
+    /// An enum written as a [u8] tag, the index of the variant, followed by
+    /// the variant's value. This is the encoding used for [Result].
+    Enum(Vec<Variant>)
+}
+
+/// A field of a [`Type::Struct`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    /// The name of the field.
+    pub name: String,
+    /// The type of the field.
+    pub ty: Type,
+    /// The revision the field was added in, like `Added<N, T>`.
+    pub added: u32,
+    /// The revision the field was removed in, like `Removed<N, T>`.
+    pub removed: Option<u32>
+}
+impl Field {
+    /// Creates a field that exists in all revisions.
+    pub fn new(name: impl Into<String>, ty: Type) -> Self {
+        Self { name: name.into(), ty, added: 0, removed: None }
+    }
+
+    /// Sets the revision the field was added in.
+    pub fn added(mut self, revision: u32) -> Self {
+        self.added = revision;
+        self
+    }
+
+    /// Sets the revision the field was removed in.
+    pub fn removed(mut self, revision: u32) -> Self {
+        self.removed = Some(revision);
+        self
+    }
+
+    /// Returns true if the field is serialized in the revision.
+    pub fn exists_in(&self, revision: u32) -> bool {
+        revision >= self.added && self.removed.is_none_or(|removed| revision < removed)
+    }
+}
+
+/// A variant of a [`Type::Enum`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    /// The name of the variant.
+    pub name: String,
+    /// The type of the variant's value, an empty [`Type::Tuple`] for unit
+    /// variants.
+    pub ty: Type
+}
+impl Variant {
+    /// Creates a new variant.
+    pub fn new(name: impl Into<String>, ty: Type) -> Self {
+        Self { name: name.into(), ty }
+    }
+}
+
+/// A dynamically typed value, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A [`Type::Bool`].
+    Bool(bool),
+    /// Any unsigned integer type and [`Type::VarInt`].
+    UInt(u128),
+    /// Any signed integer type and [`Type::Isize`].
+    Int(i128),
+    /// A [`Type::F32`].
+    F32(f32),
+    /// A [`Type::F64`].
+    F64(f64),
+    /// A [`Type::Char`].
+    Char(char),
+    /// A [`Type::String`].
+    String(String),
+    /// A [`Type::Bytes`].
+    Bytes(Vec<u8>),
+    /// The elements of a [`Type::Array`], [`Type::List`] or [`Type::Tuple`].
+    List(Vec<Value>),
+    /// The entries of a [`Type::Map`] in serialized order.
+    Map(Vec<(Value, Value)>),
+    /// A [`Type::Option`].
+    Option(Option<Box<Value>>),
+    /// The fields of a [`Type::Struct`] that exist in the revision of the data.
+    Struct(Vec<(String, Value)>),
+    /// The variant name and value of a [`Type::Enum`].
+    Enum(String, Box<Value>)
+}
+
+fn int<T: Deserialize + Into<i128>, D: Decoder>(d: &mut D) -> BinverseResult<Value> {
+    d.deserialize::<T>().map(|x| Value::Int(x.into()))
+}
+fn uint<T: Deserialize + Into<u128>, D: Decoder>(d: &mut D) -> BinverseResult<Value> {
+    d.deserialize::<T>().map(|x| Value::UInt(x.into()))
+}
+fn write_int<T: Serialize + TryFrom<i128>, E: Encoder>(s: &mut E, x: i128) -> BinverseResult<()> {
+    T::try_from(x).map_err(|_| BinverseError::IntegerOverflow)?.serialize(s)
+}
+fn write_uint<T: Serialize + TryFrom<u128>, E: Encoder>(s: &mut E, x: u128) -> BinverseResult<()> {
+    T::try_from(x).map_err(|_| BinverseError::IntegerOverflow)?.serialize(s)
+}
+
+impl Value {
+    /// Decodes a value of the type. Struct fields are read depending on the
+    /// revision of the deserializer.
+    pub fn decode<D: Decoder>(d: &mut D, ty: &Type) -> BinverseResult<Self> {
+        Ok(match ty {
+            Type::Bool => Value::Bool(d.deserialize()?),
+            Type::U8 => uint::<u8, D>(d)?,
+            Type::U16 => uint::<u16, D>(d)?,
+            Type::U32 => uint::<u32, D>(d)?,
+            Type::U64 => uint::<u64, D>(d)?,
+            Type::U128 => uint::<u128, D>(d)?,
+            Type::I8 => int::<i8, D>(d)?,
+            Type::I16 => int::<i16, D>(d)?,
+            Type::I32 => int::<i32, D>(d)?,
+            Type::I64 => int::<i64, D>(d)?,
+            Type::I128 => int::<i128, D>(d)?,
+            Type::VarInt => Value::UInt(d.read_varint()?.into()),
+            Type::Isize => Value::Int(varint::unzigzag(d.read_varint()?).into()),
+            Type::F32 => Value::F32(d.deserialize()?),
+            Type::F64 => Value::F64(d.deserialize()?),
+            Type::Char => Value::Char(d.deserialize()?),
+            Type::String(sb) => Value::String(d.deserialize_sized(*sb)?),
+            Type::Bytes(sb) => {
+                let size = d.read_size(*sb)?;
+                Value::Bytes(read_bytes(d, size)?)
+            }
+            Type::Array(ty, len) => Value::List(
+                (0..*len).map(|_| Value::decode(d, ty)).collect::<BinverseResult<_>>()?
+            ),
+            Type::List(ty, sb) => {
+                let size = d.read_size(*sb)?;
+                Value::List((0..size).map(|_| Value::decode(d, ty)).collect::<BinverseResult<_>>()?)
+            }
+            Type::Map(key, value, sb) => {
+                let size = d.read_size(*sb)?;
+                Value::Map((0..size)
+                    .map(|_| Ok((Value::decode(d, key)?, Value::decode(d, value)?)))
+                    .collect::<BinverseResult<_>>()?)
+            }
+            Type::Option(ty) => Value::Option(match d.deserialize()? {
+                0_u8 => None,
+                1_u8 => Some(Box::new(Value::decode(d, ty)?)),
+                _ => return Err(BinverseError::InvalidData)
+            }),
+            Type::Tuple(types) => Value::List(
+                types.iter().map(|ty| Value::decode(d, ty)).collect::<BinverseResult<_>>()?
+            ),
+            Type::Struct(fields) => {
+                let revision = d.revision();
+                Value::Struct(fields.iter()
+                    .filter(|field| field.exists_in(revision))
+                    .map(|field| Ok((field.name.clone(), Value::decode(d, &field.ty)?)))
+                    .collect::<BinverseResult<_>>()?)
+            }
+            Type::Enum(variants) => {
+                let tag: u8 = d.deserialize()?;
+                let variant = variants.get(tag as usize).ok_or(BinverseError::InvalidData)?;
+                Value::Enum(variant.name.clone(), Box::new(Value::decode(d, &variant.ty)?))
+            }
+        })
+    }
+
+    /// Encodes the value as the type. Returns [`BinverseError::InvalidData`]
+    /// if the value doesn't match the type and
+    /// [`BinverseError::IntegerOverflow`] if an integer doesn't fit.
+    /// Struct values are encoded with the fields they contain, which have
+    /// to be in the order of the type's fields.
+    pub fn encode<E: Encoder>(&self, s: &mut E, ty: &Type) -> BinverseResult<()> {
+        match (ty, self) {
+            (Type::Bool, Value::Bool(x)) => x.serialize(s),
+            (Type::U8, Value::UInt(x)) => write_uint::<u8, E>(s, *x),
+            (Type::U16, Value::UInt(x)) => write_uint::<u16, E>(s, *x),
+            (Type::U32, Value::UInt(x)) => write_uint::<u32, E>(s, *x),
+            (Type::U64, Value::UInt(x)) => write_uint::<u64, E>(s, *x),
+            (Type::U128, Value::UInt(x)) => x.serialize(s),
+            (Type::I8, Value::Int(x)) => write_int::<i8, E>(s, *x),
+            (Type::I16, Value::Int(x)) => write_int::<i16, E>(s, *x),
+            (Type::I32, Value::Int(x)) => write_int::<i32, E>(s, *x),
+            (Type::I64, Value::Int(x)) => write_int::<i64, E>(s, *x),
+            (Type::I128, Value::Int(x)) => x.serialize(s),
+            (Type::VarInt, Value::UInt(x)) => {
+                s.write_varint(u64::try_from(*x).map_err(|_| BinverseError::IntegerOverflow)?)
+            }
+            (Type::Isize, Value::Int(x)) => {
+                let x = i64::try_from(*x).map_err(|_| BinverseError::IntegerOverflow)?;
+                s.write_varint(varint::zigzag(x))
+            }
+            (Type::F32, Value::F32(x)) => x.serialize(s),
+            (Type::F64, Value::F64(x)) => x.serialize(s),
+            (Type::Char, Value::Char(x)) => x.serialize(s),
+            (Type::String(sb), Value::String(x)) => s.serialize_sized(*sb, x),
+            (Type::Bytes(sb), Value::Bytes(x)) => {
+                s.write_size(*sb, x.len())?;
+                s.write(x)
+            }
+            (Type::Array(ty, len), Value::List(values)) if values.len() == *len => {
+                values.iter().try_for_each(|value| value.encode(s, ty))
+            }
+            (Type::List(ty, sb), Value::List(values)) => {
+                s.write_size(*sb, values.len())?;
+                values.iter().try_for_each(|value| value.encode(s, ty))
+            }
+            (Type::Map(key_ty, value_ty, sb), Value::Map(entries)) => {
+                s.write_size(*sb, entries.len())?;
+                entries.iter().try_for_each(|(key, value)| {
+                    key.encode(s, key_ty)?;
+                    value.encode(s, value_ty)
+                })
+            }
+            (Type::Option(_), Value::Option(None)) => 0_u8.serialize(s),
+            (Type::Option(ty), Value::Option(Some(value))) => {
+                1_u8.serialize(s)?;
+                value.encode(s, ty)
+            }
+            (Type::Tuple(types), Value::List(values)) if values.len() == types.len() => {
+                types.iter().zip(values).try_for_each(|(ty, value)| value.encode(s, ty))
+            }
+            (Type::Struct(fields), Value::Struct(values)) => {
+                let mut fields = fields.iter();
+                for (name, value) in values {
+                    let field = fields.find(|field| field.name == *name).ok_or(BinverseError::InvalidData)?;
+                    value.encode(s, &field.ty)?;
+                }
+                Ok(())
+            }
+            (Type::Enum(variants), Value::Enum(name, value)) => {
+                let tag = variants.iter().position(|variant| variant.name == *name)
+                    .and_then(|tag| u8::try_from(tag).ok())
+                    .ok_or(BinverseError::InvalidData)?;
+                tag.serialize(s)?;
+                value.encode(s, &variants[tag as usize].ty)
+            }
+            _ => Err(BinverseError::InvalidData)
+        }
+    }
+
+    /// Returns the value of a struct field by name.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Struct(fields) => fields.iter().find(|(n, _)| n == name).map(|(_, value)| value),
+            _ => None
+        }
+    }
+}
@@ -1,5 +1,4 @@
-use std::io::{Read, Write};
-use crate::{error::{BinverseResult, BinverseError}, serialize::{Serialize, Deserialize}};
+use crate::{error::{BinverseResult, BinverseError}, io::{Read, Write}, serialize::{Serialize, Deserialize}, streams::{Decoder, Encoder}};
 
 /// The maximum length in bytes of a varint (u64)
 pub const MAX_LEN: usize = 10;
@@ -25,6 +24,12 @@ pub fn read<R: Read>(mut r: R) -> BinverseResult<u64> {
     Err(BinverseError::VarIntOverflow)
 }
 
+/// Returns the number of bytes needed to write `x` as a varint.
+pub const fn len(x: u64) -> usize {
+    let bits = 64 - x.leading_zeros() as usize;
+    if bits == 0 { 1 } else { bits.div_ceil(7) }
+}
+
 /// Writes an unsigned 64-bit varint number to a Writer
 pub fn write<W: Write>(mut x: u64, mut w: W) -> Result<(), BinverseError> {
     while x >= 0x80 {
@@ -35,18 +40,31 @@ pub fn write<W: Write>(mut x: u64, mut w: W) -> Result<(), BinverseError> {
     Ok(())
 }
 
+/// Maps a signed number to an unsigned one so numbers close to zero stay
+/// small: 0, -1, 1, -2, ... become 0, 1, 2, 3, ...
+pub const fn zigzag(x: i64) -> u64 {
+    ((x << 1) ^ (x >> 63)) as u64
+}
+
+/// The inverse of [zigzag].
+pub const fn unzigzag(x: u64) -> i64 {
+    ((x >> 1) as i64) ^ -((x & 1) as i64)
+}
 
 /// Convenience wrapper type to read and write varints.
 #[repr(transparent)]
 #[derive(Debug, Clone, Copy, Hash, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct VarInt(pub u64);
 impl Serialize for VarInt {
-    fn serialize<W: Write>(&self, s: &mut crate::streams::Serializer<W>) -> BinverseResult<()> {
-        write(self.0, &mut s.w)
+    fn serialize<E: Encoder>(&self, s: &mut E) -> BinverseResult<()> {
+        s.write_varint(self.0)
     }
 }
 impl Deserialize for VarInt {
-    fn deserialize<R: Read>(d: &mut crate::streams::Deserializer<R>) -> BinverseResult<Self> {
-        read(&mut d.r).map(Self)
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        d.read_varint().map(Self)
+    }
+    fn skip<D: Decoder>(d: &mut D) -> BinverseResult<()> {
+        d.read_varint().map(drop)
     }
 }
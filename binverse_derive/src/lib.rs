@@ -6,7 +6,7 @@
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{self, Expr, Fields, FieldsNamed, FieldsUnnamed, GenericArgument, Item, ItemStruct, punctuated::{Pair, Punctuated}};
+use syn::{self, ext::IdentExt, Expr, Fields, FieldsNamed, FieldsUnnamed, GenericArgument, Item, ItemStruct, punctuated::{Pair, Punctuated}};
 
 #[derive(Clone, Copy)]
 enum SizeBytes {
@@ -30,7 +30,7 @@ impl TryFrom<u32> for SizeBytes {
     }
 }
 impl SizeBytes {
-    fn to_ident(&self) -> proc_macro2::Ident {
+    fn to_ident(self) -> proc_macro2::Ident {
         use SizeBytes::*;
         proc_macro2::Ident::new(match self {
             One => "One",
@@ -46,13 +46,32 @@ impl SizeBytes {
 /// All members also have to implement Serialize and Deserialize.
 /// Members can be annotated with the following attributes:
 /// - SizeBytes<N, T> to set the size bytes of a data structure. N can be 1, 2, 4 or 8.
-/// - Added<N, T> states that the member was added in revision N.
+/// - Added<N, T> states that the member was added in revision N. It is not
+///   serialized when writing data for an older revision.
 /// - Removed<N, T> states that the member was removed in revision N. 
-/// This also removes the member from the struct, it will only be used in the
-///  Deserialize implementation to skip it in old data.
+///   This also removes the member from the struct, it will only be used in the
+///   Deserialize implementation to skip it in old data using `Deserialize::skip`.
+///   The length of the data can only be known from the type, so T still has to
+///   exist and implement Deserialize after the field was removed. Types that are
+///   no longer used elsewhere can be kept in a module for old revisions.
+///   When serializing for a revision before N, `T::default()` is written in its
+///   place, or `BinverseError::RemovedFieldWithoutDefault` is returned if T
+///   doesn't implement Default.
 /// 
 /// The attributes can be chained in any meaningful order. 
 /// SizeBytes always has to be the innermost attribute if present.
+///
+/// With `#[serializable(projection)]`, a `{Name}Projection` struct with all
+/// fields wrapped in an `Option` and a `{Name}::project(d, &["a", "b"])`
+/// function are generated for structs with fields. It only deserializes the
+/// named fields and skips all others, which is useful when only a few fields
+/// of large records are needed.
+/// With `#[serializable(migrate)]`, root objects read by `binverse::read` are
+/// deserialized using the `binverse::migrate::Migrate` impl of the struct, so
+/// data written with older versions of the type is converted to the current
+/// one. Both arguments can be combined: `#[serializable(projection, migrate)]`.
+/// The generated `deserialize_in_place` reads all fields in place to reuse
+/// their allocations and `skip` skips all fields without deserializing them.
 /// 
 /// # Example
 /// ```ignore
@@ -66,14 +85,20 @@ impl SizeBytes {
 /// }
 /// ```
 pub fn serializable(attr: TokenStream, input: TokenStream) -> TokenStream {
-    if !attr.is_empty() {
-        panic!("No attributes expected")
+    let (mut projection, mut migrate) = (false, false);
+    for arg in attr.to_string().split(',').map(str::trim).filter(|arg| !arg.is_empty()) {
+        match arg {
+            "projection" => projection = true,
+            "migrate" => migrate = true,
+            _ => panic!("Unexpected attribute argument '{}', only 'projection' and 'migrate' are supported", arg)
+        }
     }
     let ast = syn::parse_macro_input!(input);
 
-    impl_serializable(ast)
+    impl_serializable(ast, projection, migrate)
 }
 
+#[allow(clippy::large_enum_variant)]
 enum AttributedField {
     Added(Box<AttributedField>, u32),
     Removed(Box<AttributedField>, u32),
@@ -94,9 +119,39 @@ impl AttributedField {
     }
 
     fn is_removed(&self) -> bool {
-        match self {
-            AttributedField::Removed(_, _) => true,
-            _ => false
+        matches!(self, AttributedField::Removed(_, _))
+    }
+
+    fn deserialize_expr(&self) -> proc_macro2::TokenStream {
+        match self.size_bytes() {
+            Some(sb) => {
+                let sb_ident = sb.to_ident();
+                quote! { binverse::streams::Decoder::deserialize_sized(d, binverse::serialize::SizeBytes::#sb_ident)? }
+            },
+            _ => quote! { binverse::serialize::Deserialize::deserialize(d)? }
+        }
+    }
+
+    /// Skips the field without deserializing it if it exists in the revision.
+    fn skip_stmt(&self) -> proc_macro2::TokenStream {
+        let ty = self.inner_ty();
+        let skip_expr = match self.size_bytes() {
+            Some(sb) => {
+                let sb_ident = sb.to_ident();
+                quote! { binverse::streams::Decoder::skip_sized::<#ty>(d, binverse::serialize::SizeBytes::#sb_ident)? }
+            },
+            None => quote! { <#ty as binverse::serialize::Deserialize>::skip(d)? }
+        };
+        let patterns = self.deserialize_patterns();
+        if patterns.is_empty() {
+            quote! { #skip_expr; }
+        } else {
+            quote! {
+                match binverse::streams::Decoder::revision(d) {
+                    #(#patterns)|* => #skip_expr,
+                    _ => ()
+                }
+            }
         }
     }
 
@@ -130,7 +185,7 @@ impl AttributedField {
                         AddPatResult::Normal => 0
                     };
                     let end_revision = revision - 1;
-                    patterns.push(quote! { #start_revision..=#end_revision }.into());
+                    patterns.push(quote! { #start_revision..=#end_revision });
                     AddPatResult::Removed(*revision)
                 },
                 AttributedField::Normal(_, _) => AddPatResult::Normal
@@ -138,7 +193,7 @@ impl AttributedField {
         }
         
         match add_pat(self, &mut patterns) {
-            AddPatResult::Added(revision) => patterns.push(quote! { #revision.. }.into()),
+            AddPatResult::Added(revision) => patterns.push(quote! { #revision.. }),
             AddPatResult::Removed(_) | AddPatResult::Normal => ()
         }
 
@@ -181,7 +236,7 @@ impl From<syn::Type> for AttributedField {
                         _ => panic!("{}", SIZE_BYTES_ERR)
                     };
                     
-                    if let Some(_) = args.next() {
+                    if args.next().is_some() {
                         panic!("{}", SIZE_BYTES_ERR);
                     }
                     let size_bytes = match size_bytes {
@@ -227,9 +282,11 @@ impl From<syn::Type> for AttributedField {
     }
 }
 
-fn impl_serializable(ast: Item) -> TokenStream {
-    // returns the new fields, the serialize TokenStream and the deserialize TokenStream
-    fn struct_impl(fields: Punctuated<syn::Field, syn::token::Comma>, named: bool) -> (Fields, proc_macro2::TokenStream, proc_macro2::TokenStream) {
+fn impl_serializable(ast: Item, projection: bool, migrate: bool) -> TokenStream {
+    // returns the new fields, the serialize TokenStream, the deserialize TokenStream, the
+    // deserialize_in_place TokenStream, the skip TokenStream and the fields and body of the projection
+    #[allow(clippy::type_complexity)]
+    fn struct_impl(fields: Punctuated<syn::Field, syn::token::Comma>, named: bool, projection_ident: &syn::Ident) -> (Fields, proc_macro2::TokenStream, proc_macro2::TokenStream, Option<proc_macro2::TokenStream>, proc_macro2::TokenStream, Option<(proc_macro2::TokenStream, proc_macro2::TokenStream)>) {
         let attr_fields: Vec<(syn::Field, Option<syn::token::Comma>, AttributedField)> =
             fields.into_pairs()
             .map(|field| match field {
@@ -244,27 +301,45 @@ fn impl_serializable(ast: Item) -> TokenStream {
             })
             .collect();
         
-        let serializers = attr_fields.iter()
-            // only serialize the field if it wasn't marked as 'Removed'
-            .filter(|(_, _, attr_field)| !attr_field.is_removed())
-            // enumerate for tuple struct indices
-            .enumerate()
-            .map(|(i, (field, _, attr_field))| -> proc_macro2::TokenStream {
-                let name = if named {
-                    let ident = &field.ident;
-                    quote! { #ident }
+        let mut kept_index = 0;
+        let serializers = attr_fields.iter().map(|(field, _, attr_field)| -> proc_macro2::TokenStream {
+                // removed fields are written with their default value for revisions that still contain them
+                let value = if attr_field.is_removed() {
+                    let ty = attr_field.inner_ty();
+                    quote! { &{
+                        use binverse::serialize::removed::{WithDefault as _, WithoutDefault as _};
+                        (&binverse::serialize::removed::RemovedField::<#ty>::new()).value()?
+                    } }
                 } else {
-                    let index = syn::Index::from(i);
-                    quote! { #index }
+                    let name = if named {
+                        let ident = &field.ident;
+                        quote! { #ident }
+                    } else {
+                        let index = syn::Index::from(kept_index);
+                        quote! { #index }
+                    };
+                    kept_index += 1;
+                    quote! { &self.#name }
                 };
-                match attr_field.size_bytes() {
+                let serialize = match attr_field.size_bytes() {
                     Some(sb) => {
                         let sb_ident = sb.to_ident();
-                        quote! { binverse::streams::Serializer::serialize_sized(s, binverse::serialize::SizeBytes::#sb_ident, &self.#name)?; }
+                        quote! { binverse::streams::Encoder::serialize_sized(s, binverse::serialize::SizeBytes::#sb_ident, #value)?; }
                     },
-                    None => quote! { binverse::serialize::Serialize::serialize(&self.#name, s)?; }.into()
+                    None => quote! { binverse::serialize::Serialize::serialize(#value, s)?; }
+                };
+                // fields that don't exist in the target revision are omitted
+                let patterns = attr_field.deserialize_patterns();
+                if patterns.is_empty() {
+                    serialize
+                } else {
+                    quote! {
+                        if ::core::matches!(binverse::streams::Encoder::revision(s), #(#patterns)|*) {
+                            #serialize
+                        }
+                    }
                 }
-            });
+            }).collect::<Vec<_>>();
 
         let mut next_deserialize_index: usize = 0;
         let mut next_deserialize_binding_name = |field: &syn::Field| {
@@ -278,43 +353,34 @@ fn impl_serializable(ast: Item) -> TokenStream {
             }
         };
 
-        let deserializers = attr_fields.iter().map(|(field, _, attr_field)| {
-            let ty = attr_field.inner_ty();
+        // reads a field that isn't removed, using the default value if it
+        // doesn't exist in the revision
+        let read_expr = |attr_field: &AttributedField| {
+            let deserialize_expr = attr_field.deserialize_expr();
             let patterns = attr_field.deserialize_patterns();
-
-            let deserialize_expr = match attr_field.size_bytes() {
-                Some(sb) => {
-                    let sb_ident = sb.to_ident();
-                    quote! { binverse::streams::Deserializer::deserialize_sized(d, binverse::serialize::SizeBytes::#sb_ident)? }
-                },
-                _ => quote! { binverse::serialize::Deserialize::deserialize(d)? }
-            };
-
             if patterns.is_empty() {
-                assert!(!attr_field.is_removed(), "Internal error: No-pattern deserialize attribute was removed");
-                let name = next_deserialize_binding_name(field);
-                quote! { let #name = #deserialize_expr; }
+                deserialize_expr
             } else {
-                let patterns = patterns.iter();
-                if attr_field.is_removed() {
-                    quote! {
-                        match d.revision() {
-                            #(#patterns)|* => { let _: #ty = #deserialize_expr; },
-                            _ => ()
-                        }
-                    }
-                } else {
-                    let name = next_deserialize_binding_name(field);
-                    quote! {
-                        let #name = match d.revision() {
-                            #(#patterns)|* => #deserialize_expr,
-                            _ => std::default::Default::default()
-                        };
+                quote! {
+                    match binverse::streams::Decoder::revision(d) {
+                        #(#patterns)|* => #deserialize_expr,
+                        _ => ::core::default::Default::default()
                     }
                 }
             }
-        });
-        let deserialize_inits = attr_fields.iter()
+        };
+
+        let deserializers = attr_fields.iter().map(|(field, _, attr_field)| {
+            if attr_field.is_removed() {
+                // removed fields are skipped without deserializing them
+                attr_field.skip_stmt()
+            } else {
+                let name = next_deserialize_binding_name(field);
+                let read_expr = read_expr(attr_field);
+                quote! { let #name = #read_expr; }
+            }
+        }).collect::<Vec<_>>();
+        let deserialize_inits: Vec<_> = attr_fields.iter()
             // filter out fields that have been removed
             .filter(|(_, _, attr_field)| !attr_field.is_removed())
             .enumerate()
@@ -324,7 +390,106 @@ fn impl_serializable(ast: Item) -> TokenStream {
             } else {
                 let ident = &syn::Ident::new(&format!("f{}", i), proc_macro2::Span::call_site());
                 quote! { #ident }
-            });
+            })
+            .collect();
+
+        // in-place deserialization reuses the existing field values
+        let in_place_readers = {
+            let mut kept_index = 0;
+            attr_fields.iter().map(|(field, _, attr_field)| {
+                if attr_field.is_removed() {
+                    return attr_field.skip_stmt();
+                }
+                let name = if named {
+                    let ident = &field.ident;
+                    quote! { #ident }
+                } else {
+                    let index = syn::Index::from(kept_index);
+                    quote! { #index }
+                };
+                kept_index += 1;
+                let read = match attr_field.size_bytes() {
+                    Some(sb) => {
+                        let sb_ident = sb.to_ident();
+                        quote! { binverse::streams::Decoder::deserialize_sized_in_place(d, &mut self.#name, binverse::serialize::SizeBytes::#sb_ident)? }
+                    },
+                    None => quote! { binverse::serialize::Deserialize::deserialize_in_place(&mut self.#name, d)? }
+                };
+                let patterns = attr_field.deserialize_patterns();
+                if patterns.is_empty() {
+                    quote! { #read; }
+                } else {
+                    quote! {
+                        match binverse::streams::Decoder::revision(d) {
+                            #(#patterns)|* => #read,
+                            _ => self.#name = ::core::default::Default::default()
+                        }
+                    }
+                }
+            }).collect::<Vec<_>>()
+        };
+
+        let skippers = attr_fields.iter().map(|(_, _, attr_field)| attr_field.skip_stmt());
+
+        // the projection only deserializes the selected fields and skips the others
+        let kept_fields = attr_fields.iter().filter(|(_, _, attr_field)| !attr_field.is_removed());
+        let field_names = kept_fields.clone().enumerate().map(|(i, (field, _, _))| match &field.ident {
+            Some(ident) => ident.unraw().to_string(),
+            None => i.to_string()
+        }).collect::<Vec<_>>();
+        let projection_fields = kept_fields.clone().zip(&field_names).map(|((field, _, attr_field), name)| {
+            let vis = &field.vis;
+            let ty = attr_field.inner_ty();
+            let doc = format!("The value of `{}` if it was selected.", name);
+            match &field.ident {
+                Some(ident) => quote! { #[doc = #doc] #vis #ident: ::core::option::Option<#ty> },
+                None => quote! { #[doc = #doc] #vis ::core::option::Option<#ty> }
+            }
+        });
+        let projection_fields = if named {
+            quote! { { #(#projection_fields),* } }
+        } else {
+            quote! { ( #(#projection_fields),* ); }
+        };
+        let mut kept_index = 0;
+        let projectors = attr_fields.iter().map(|(field, _, attr_field)| {
+            let skip = attr_field.skip_stmt();
+            if attr_field.is_removed() {
+                return skip;
+            }
+            let name = &field_names[kept_index];
+            let binding = match &field.ident {
+                Some(ident) => quote! { #ident },
+                None => {
+                    let ident = syn::Ident::new(&format!("f{}", kept_index), proc_macro2::Span::call_site());
+                    quote! { #ident }
+                }
+            };
+            kept_index += 1;
+            let read_expr = read_expr(attr_field);
+            quote! {
+                let #binding = if __fields.contains(&#name) {
+                    ::core::option::Option::Some(#read_expr)
+                } else {
+                    #skip
+                    ::core::option::Option::None
+                };
+            }
+        }).collect::<Vec<_>>();
+        let projection_inits = deserialize_inits.clone();
+        let project = quote! {
+            for (index, field) in __fields.iter().enumerate() {
+                if ![#(#field_names),*].contains(field) {
+                    return Err(binverse::error::BinverseError::UnknownField { index });
+                }
+            }
+            #(#projectors)*
+        };
+        let project = if named {
+            quote! { #project Ok(#projection_ident { #(#projection_inits),* }) }
+        } else {
+            quote! { #project Ok(#projection_ident(#(#projection_inits),*)) }
+        };
 
         let mut new_fields = Punctuated::new();
         attr_fields.iter()
@@ -364,17 +529,24 @@ fn impl_serializable(ast: Item) -> TokenStream {
                     #(#deserializers)*
                     Ok(Self(#(#deserialize_inits),*))
                 }
-            }
+            },
+            Some(quote! {
+                #(#in_place_readers)*
+                Ok(())
+            }),
+            quote! { #(#skippers)* },
+            Some((projection_fields, project))
         )
     }
 
 
     match ast {
         Item::Struct(s) => {
-            let (new_fields, serialize, deserialize) = match s.fields {
-                Fields::Unit => (Fields::Unit, quote! { }, quote! { Ok(Self) }),
-                Fields::Named(fields) => struct_impl(fields.named, true),
-                Fields::Unnamed(fields) => struct_impl(fields.unnamed, false)
+            let projection_ident = syn::Ident::new(&format!("{}Projection", s.ident), s.ident.span());
+            let (new_fields, serialize, deserialize, in_place, skip, projection_impl) = match s.fields {
+                Fields::Unit => (Fields::Unit, quote! { }, quote! { Ok(Self) }, None, quote! { }, None),
+                Fields::Named(fields) => struct_impl(fields.named, true, &projection_ident),
+                Fields::Unnamed(fields) => struct_impl(fields.unnamed, false, &projection_ident)
             };
 
             let new_struct = ItemStruct {
@@ -383,6 +555,42 @@ fn impl_serializable(ast: Item) -> TokenStream {
             };
             
             let ident = new_struct.ident.clone();
+            let vis = new_struct.vis.clone();
+            let in_place = in_place.map(|in_place| quote! {
+                #[inline]
+                fn deserialize_in_place<D: ::binverse::streams::Decoder>(&mut self, d: &mut D) -> ::binverse::error::BinverseResult<()> {
+                    #in_place
+                }
+            });
+            let migrate = migrate.then(|| quote! {
+                #[inline]
+                fn deserialize_root<D: ::binverse::streams::Decoder>(d: &mut D) -> ::binverse::error::BinverseResult<Self> {
+                    <Self as ::binverse::migrate::Migrate>::deserialize_migrated(d)
+                }
+            });
+            let projection = projection_impl.filter(|_| projection).map(|(fields, project)| {
+                let struct_doc = format!("The selected fields of a [`{}`], returned by [`{}::project`].", ident, ident);
+                quote! {
+                    #[doc = #struct_doc]
+                    #[allow(dead_code)]
+                    #vis struct #projection_ident #fields
+
+                    #[automatically_derived]
+                    #[allow(unused_qualifications, dead_code)]
+                    impl #ident {
+                        /// Deserializes only the fields named in `__fields` and skips all other
+                        /// fields without deserializing them. Fields that don't exist in the
+                        /// revision get their default value like in the full deserialization and
+                        /// the same number of bytes is read.
+                        ///
+                        /// Returns `BinverseError::UnknownField` if a name isn't a field of
+                        /// the struct. Fields of tuple structs are named by their index.
+                        #vis fn project<D: ::binverse::streams::Decoder>(d: &mut D, __fields: &[&str]) -> ::binverse::error::BinverseResult<#projection_ident> {
+                            #project
+                        }
+                    }
+                }
+            });
             let new_ast = Item::Struct(new_struct);
             quote! {
                 #new_ast
@@ -391,7 +599,7 @@ fn impl_serializable(ast: Item) -> TokenStream {
                 #[allow(unused_qualifications)]
                 impl ::binverse::serialize::Serialize for #ident {
                     #[inline]
-                    fn serialize<W: ::std::io::Write>(&self, s: &mut ::binverse::streams::Serializer<W>) -> ::binverse::error::BinverseResult<()> {
+                    fn serialize<E: ::binverse::streams::Encoder>(&self, s: &mut E) -> ::binverse::error::BinverseResult<()> {
                         #serialize
                         Ok(())
                     }
@@ -400,10 +608,19 @@ fn impl_serializable(ast: Item) -> TokenStream {
                 #[allow(unused_qualifications)]
                 impl ::binverse::serialize::Deserialize for #ident {
                     #[inline]
-                    fn deserialize<R: ::std::io::Read>(d: &mut ::binverse::streams::Deserializer<R>) -> ::binverse::error::BinverseResult<Self> {
+                    fn deserialize<D: ::binverse::streams::Decoder>(d: &mut D) -> ::binverse::error::BinverseResult<Self> {
                         #deserialize
                     }
+                    #in_place
+                    #[inline]
+                    fn skip<D: ::binverse::streams::Decoder>(d: &mut D) -> ::binverse::error::BinverseResult<()> {
+                        #skip
+                        Ok(())
+                    }
+                    #migrate
                 }
+
+                #projection
             }.into()
         },
         Item::Enum(_e) => {
@@ -0,0 +1,133 @@
+use binverse::{serialize::SizeBytes, value::{Field, Type, Variant}};
+use serde_json::{Map, Value as Json};
+
+use crate::{Error, Path};
+
+/// Parses a type layout from JSON. Types are written as:
+///
+/// - A string for primitive types: `"bool"`, `"u8"` to `"u128"`, `"i8"` to
+///   `"i128"`, `"varint"`, `"usize"`, `"isize"`, `"f32"`, `"f64"`, `"char"`
+///   and `"string"`/`"bytes"` with a varint length.
+/// - `{"string": 1}` or `{"bytes": 2}` for a length with fixed size bytes
+///   (1, 2, 4 or 8; `"var"` for a varint).
+/// - `{"array": T, "len": 4}` for fixed size arrays.
+/// - `{"list": T}` and `{"map": [K, V]}` for collections, optionally with
+///   `"size_bytes"`.
+/// - `{"option": T}` and `{"tuple": [T, ...]}`.
+/// - `{"struct": [{"name": "a", "type": T, "added": 2, "removed": 5}, ...]}`
+///   where `added` and `removed` are optional.
+/// - `{"enum": [{"name": "A", "type": T}, ...]}` where the type is optional
+///   for unit variants.
+pub fn parse_layout(json: &Json) -> Result<Type, Error> {
+    parse_type(json, &mut Path::default())
+}
+
+fn parse_type(json: &Json, path: &mut Path) -> Result<Type, Error> {
+    match json {
+        Json::String(name) => primitive(name).ok_or_else(|| path.error(format!("unknown type '{}'", name))),
+        Json::Object(obj) => parse_object(obj, path),
+        _ => Err(path.error("expected a type name or object"))
+    }
+}
+
+fn primitive(name: &str) -> Option<Type> {
+    Some(match name {
+        "bool" => Type::Bool,
+        "u8" => Type::U8,
+        "u16" => Type::U16,
+        "u32" => Type::U32,
+        "u64" => Type::U64,
+        "u128" => Type::U128,
+        "i8" => Type::I8,
+        "i16" => Type::I16,
+        "i32" => Type::I32,
+        "i64" => Type::I64,
+        "i128" => Type::I128,
+        "varint" | "usize" => Type::VarInt,
+        "isize" => Type::Isize,
+        "f32" => Type::F32,
+        "f64" => Type::F64,
+        "char" => Type::Char,
+        "string" => Type::String(SizeBytes::Var),
+        "bytes" => Type::Bytes(SizeBytes::Var),
+        _ => return None
+    })
+}
+
+fn size_bytes(json: Option<&Json>, path: &mut Path) -> Result<SizeBytes, Error> {
+    Ok(match json {
+        None => SizeBytes::Var,
+        Some(Json::String(s)) if s == "var" => SizeBytes::Var,
+        Some(json) => match json.as_u64() {
+            Some(1) => SizeBytes::One,
+            Some(2) => SizeBytes::Two,
+            Some(4) => SizeBytes::Four,
+            Some(8) => SizeBytes::Eight,
+            _ => return Err(path.error("size bytes have to be 1, 2, 4, 8 or \"var\""))
+        }
+    })
+}
+
+const KINDS: [&str; 9] = ["string", "bytes", "array", "list", "map", "option", "tuple", "struct", "enum"];
+
+fn parse_object(obj: &Map<String, Json>, path: &mut Path) -> Result<Type, Error> {
+    let (kind, inner) = obj.iter()
+        .find(|(key, _)| KINDS.contains(&key.as_str()))
+        .ok_or_else(|| path.error(format!("expected one of the keys {}", KINDS.join(", "))))?;
+    let key = kind.clone();
+    path.with_key(&key, |path| Ok(match kind.as_str() {
+        "string" => Type::String(size_bytes(Some(inner), path)?),
+        "bytes" => Type::Bytes(size_bytes(Some(inner), path)?),
+        "array" => {
+            let len = obj.get("len").and_then(Json::as_u64).ok_or_else(|| path.error("array needs a 'len'"))?;
+            let len = usize::try_from(len).map_err(|_| path.error("array length too large"))?;
+            Type::Array(Box::new(parse_type(inner, path)?), len)
+        }
+        "list" => Type::List(Box::new(parse_type(inner, path)?), size_bytes(obj.get("size_bytes"), path)?),
+        "map" => match inner.as_array().map(Vec::as_slice) {
+            Some([key, value]) => Type::Map(
+                Box::new(path.with_index(0, |path| parse_type(key, path))?),
+                Box::new(path.with_index(1, |path| parse_type(value, path))?),
+                size_bytes(obj.get("size_bytes"), path)?
+            ),
+            _ => return Err(path.error("map needs a [key, value] type pair"))
+        },
+        "option" => Type::Option(Box::new(parse_type(inner, path)?)),
+        "tuple" => Type::Tuple(elements(inner, path, parse_type)?),
+        "struct" => Type::Struct(elements(inner, path, parse_field)?),
+        _ => Type::Enum(elements(inner, path, parse_variant)?)
+    }))
+}
+
+fn elements<T>(json: &Json, path: &mut Path, f: fn(&Json, &mut Path) -> Result<T, Error>) -> Result<Vec<T>, Error> {
+    let array = json.as_array().ok_or_else(|| path.error("expected an array"))?;
+    array.iter().enumerate().map(|(i, json)| path.with_index(i, |path| f(json, path))).collect()
+}
+
+fn name(json: &Json, path: &mut Path) -> Result<String, Error> {
+    json.get("name").and_then(Json::as_str).map(String::from).ok_or_else(|| path.error("missing 'name'"))
+}
+
+fn revision(json: &Json, key: &str, path: &mut Path) -> Result<Option<u32>, Error> {
+    json.get(key).map(|r| r.as_u64().and_then(|r| u32::try_from(r).ok()).ok_or_else(|| path.error(format!("'{}' has to be a revision number", key)))).transpose()
+}
+
+fn parse_field(json: &Json, path: &mut Path) -> Result<Field, Error> {
+    let ty = json.get("type").ok_or_else(|| path.error("missing 'type'"))?;
+    let mut field = Field::new(name(json, path)?, path.with_key("type", |path| parse_type(ty, path))?);
+    if let Some(added) = revision(json, "added", path)? {
+        field = field.added(added);
+    }
+    if let Some(removed) = revision(json, "removed", path)? {
+        field = field.removed(removed);
+    }
+    Ok(field)
+}
+
+fn parse_variant(json: &Json, path: &mut Path) -> Result<Variant, Error> {
+    let ty = match json.get("type") {
+        Some(ty) => path.with_key("type", |path| parse_type(ty, path))?,
+        None => Type::Tuple(Vec::new())
+    };
+    Ok(Variant::new(name(json, path)?, ty))
+}
@@ -0,0 +1,396 @@
+//! Conversion between JSON and binverse data, for example to edit test
+//! fixtures by hand. Since binverse data doesn't describe itself, a
+//! [Type] layout for the revision of the data is needed, see
+//! [parse_layout] for the JSON format of layouts.
+//!
+//! Numbers, strings and collections are encoded exactly like the native
+//! binverse impls. In JSON, values are represented as:
+//!
+//! - Integers as numbers. Integers that don't fit into 64 bits can also be
+//!   written as strings and are output as strings.
+//! - Floats as numbers, or the strings `"NaN"`, `"inf"` and `"-inf"`. The
+//!   payload of NaNs isn't preserved.
+//! - Chars and strings as strings, bytes as arrays of numbers.
+//! - Arrays, lists and tuples as arrays.
+//! - Maps with string keys as objects, other maps as arrays of `[key, value]`
+//!   pairs.
+//! - Options as `null` or the value.
+//! - Structs as objects with the fields that exist in the revision.
+//! - Enums as `{"Variant": value}`, or just `"Variant"` for unit variants.
+//!
+//! Errors contain the JSON path of the value that caused them, like
+//! `$.users[3].name`. Keys that aren't identifiers are quoted, like
+//! `$.tags["a.b"]`.
+
+use std::fmt;
+
+use binverse::{error::BinverseError, serialize::SizeBytes, streams::{Decoder, Deserializer, Encoder, Serializer}, value::{Type, Value}};
+use serde_json::{Map, Number, Value as Json};
+
+mod layout;
+
+pub use layout::parse_layout;
+
+/// An error while converting between JSON and binverse.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing the binverse data failed.
+    Binverse(BinverseError),
+    /// A JSON value didn't match the layout or a layout was invalid.
+    Invalid {
+        /// The JSON path of the value, for example `$.users[3].name`.
+        path: String,
+        /// A description of the problem.
+        message: String
+    }
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Binverse(err) => write!(f, "{}", err),
+            Self::Invalid { path, message } => write!(f, "{}: {}", path, message)
+        }
+    }
+}
+impl std::error::Error for Error { }
+impl From<BinverseError> for Error {
+    fn from(err: BinverseError) -> Self {
+        Self::Binverse(err)
+    }
+}
+
+/// The path to the current JSON value, used for error messages.
+#[derive(Default)]
+struct Path(Vec<Segment>);
+
+enum Segment {
+    Key(String),
+    Index(usize)
+}
+
+impl Path {
+    fn with_key<T>(&mut self, key: &str, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.0.push(Segment::Key(key.to_owned()));
+        let t = f(self);
+        self.0.pop();
+        t
+    }
+
+    fn with_index<T>(&mut self, index: usize, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.0.push(Segment::Index(index));
+        let t = f(self);
+        self.0.pop();
+        t
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::Invalid { path: self.to_string(), message: message.into() }
+    }
+}
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "$")?;
+        for segment in &self.0 {
+            match segment {
+                // keys that aren't identifiers are quoted so the path stays unambiguous
+                Segment::Key(key) if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_') => write!(f, ".{}", key)?,
+                Segment::Key(key) => write!(f, "[{}]", Json::String(key.clone()))?,
+                Segment::Index(i) => write!(f, "[{}]", i)?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a value of the type and converts it to JSON. Struct fields are read
+/// depending on the revision of the decoder.
+pub fn read_json<D: Decoder>(d: &mut D, ty: &Type) -> Result<Json, Error> {
+    let value = Value::decode(d, ty)?;
+    to_json(&value, ty, &mut Path::default())
+}
+
+/// Converts JSON to the type and writes it. Struct fields have to exist
+/// depending on the revision of the encoder.
+pub fn write_json<E: Encoder>(s: &mut E, json: &Json, ty: &Type) -> Result<(), Error> {
+    let value = from_json(json, ty, s.revision(), &mut Path::default())?;
+    value.encode(s, ty)?;
+    Ok(())
+}
+
+/// Converts binverse data to JSON. If `revision` is `None`, the data starts
+/// with the revision, like data written by [binverse::write]. Returns an
+/// error if there is data left over.
+pub fn binverse_to_json(data: &[u8], ty: &Type, revision: Option<u32>) -> Result<Json, Error> {
+    let mut d = match revision {
+        Some(revision) => Deserializer::new_no_revision(data, revision),
+        None => Deserializer::new(data)?
+    };
+    let json = read_json(&mut d, ty)?;
+    let read = d.bytes_read();
+    let leftover = d.finish().len() as u64;
+    if leftover != 0 {
+        return Err(BinverseError::LengthMismatch { expected: read + leftover, found: read }.into());
+    }
+    Ok(json)
+}
+
+/// Converts JSON to binverse data for the revision. If `header` is true, the
+/// revision is written first, like [binverse::write] does.
+pub fn json_to_binverse(json: &Json, ty: &Type, revision: u32, header: bool) -> Result<Vec<u8>, Error> {
+    let mut s = if header {
+        Serializer::new(Vec::new(), revision)?
+    } else {
+        Serializer::new_no_revision(Vec::new()).for_revision(revision)
+    };
+    write_json(&mut s, json, ty)?;
+    Ok(s.finish()?)
+}
+
+fn float_to_json(x: f64, text: String) -> Json {
+    if x.is_nan() {
+        Json::String("NaN".into())
+    } else if x.is_infinite() {
+        Json::String(if x > 0.0 { "inf" } else { "-inf" }.into())
+    } else {
+        // the shortest representation of the original float type
+        serde_json::from_str::<Number>(&text).map_or(Json::Null, Json::Number)
+    }
+}
+
+fn elements_to_json<'a>(values: &[Value], types: impl Iterator<Item = &'a Type>, path: &mut Path) -> Result<Json, Error> {
+    values.iter().zip(types).enumerate()
+        .map(|(i, (value, ty))| path.with_index(i, |path| to_json(value, ty, path)))
+        .collect::<Result<_, _>>()
+        .map(Json::Array)
+}
+
+fn to_json(value: &Value, ty: &Type, path: &mut Path) -> Result<Json, Error> {
+    Ok(match (ty, value) {
+        (Type::Bool, Value::Bool(x)) => Json::Bool(*x),
+        (_, Value::UInt(x)) => match u64::try_from(*x) {
+            Ok(x) => Json::Number(x.into()),
+            Err(_) => Json::String(x.to_string())
+        },
+        (_, Value::Int(x)) => match i64::try_from(*x) {
+            Ok(x) => Json::Number(x.into()),
+            Err(_) => Json::String(x.to_string())
+        },
+        (_, Value::F32(x)) => float_to_json(*x as f64, format!("{:?}", x)),
+        (_, Value::F64(x)) => float_to_json(*x, format!("{:?}", x)),
+        (_, Value::Char(c)) => Json::String(c.to_string()),
+        (_, Value::String(s)) => Json::String(s.clone()),
+        (_, Value::Bytes(b)) => Json::Array(b.iter().map(|b| Json::Number((*b).into())).collect()),
+        (Type::Array(ty, _) | Type::List(ty, _), Value::List(values)) => elements_to_json(values, std::iter::repeat(&**ty), path)?,
+        (Type::Tuple(types), Value::List(values)) => elements_to_json(values, types.iter(), path)?,
+        (Type::Map(key_ty, value_ty, _), Value::Map(entries)) => {
+            if matches!(**key_ty, Type::String(_)) {
+                let mut obj = Map::new();
+                for (key, value) in entries {
+                    let Value::String(key) = key else { return Err(path.error("expected a string key")) };
+                    let json = path.with_key(key, |path| to_json(value, value_ty, path))?;
+                    obj.insert(key.clone(), json);
+                }
+                Json::Object(obj)
+            } else {
+                entries.iter().enumerate().map(|(i, (key, value))| path.with_index(i, |path| Ok(Json::Array(vec![
+                    to_json(key, key_ty, path)?,
+                    to_json(value, value_ty, path)?
+                ])))).collect::<Result<_, Error>>().map(Json::Array)?
+            }
+        }
+        (Type::Option(_), Value::Option(None)) => Json::Null,
+        (Type::Option(ty), Value::Option(Some(value))) => to_json(value, ty, path)?,
+        (Type::Struct(fields), Value::Struct(values)) => {
+            let mut obj = Map::new();
+            for (name, value) in values {
+                let field = fields.iter().find(|field| field.name == *name)
+                    .ok_or_else(|| path.error(format!("unknown field '{}'", name)))?;
+                let json = path.with_key(name, |path| to_json(value, &field.ty, path))?;
+                obj.insert(name.clone(), json);
+            }
+            Json::Object(obj)
+        }
+        (Type::Enum(variants), Value::Enum(name, value)) => {
+            let variant = variants.iter().find(|variant| variant.name == *name)
+                .ok_or_else(|| path.error(format!("unknown variant '{}'", name)))?;
+            if variant.ty == Type::Tuple(Vec::new()) {
+                Json::String(name.clone())
+            } else {
+                let json = path.with_key(name, |path| to_json(value, &variant.ty, path))?;
+                Json::Object([(name.clone(), json)].into_iter().collect())
+            }
+        }
+        _ => return Err(path.error("value doesn't match the layout"))
+    })
+}
+
+/// Returns the integer as a u128 if it is a non-negative number or a string
+/// containing one.
+fn json_uint(json: &Json) -> Option<u128> {
+    match json {
+        Json::Number(n) => n.as_u64().map(u128::from),
+        Json::String(s) => s.parse().ok(),
+        _ => None
+    }
+}
+fn json_int(json: &Json) -> Option<i128> {
+    match json {
+        Json::Number(n) => n.as_i64().map(i128::from),
+        Json::String(s) => s.parse().ok(),
+        _ => None
+    }
+}
+fn json_float(json: &Json) -> Option<f64> {
+    match json {
+        Json::Number(n) => n.as_f64(),
+        Json::String(s) => match s.as_str() {
+            "NaN" => Some(f64::NAN),
+            "inf" => Some(f64::INFINITY),
+            "-inf" => Some(f64::NEG_INFINITY),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+fn check_len(len: usize, sb: SizeBytes, path: &Path) -> Result<(), Error> {
+    if len as u64 > sb.maximum() {
+        return Err(path.error(format!("length {} exceeds the maximum of {} for SizeBytes::{}", len, sb.maximum(), sb.to_str())));
+    }
+    Ok(())
+}
+
+/// Converts the elements of a JSON array. If `len` is set, the array has to
+/// have exactly this number of elements.
+fn elements_from_json<'a>(json: &Json, types: impl Iterator<Item = &'a Type>, len: Option<usize>, revision: u32, path: &mut Path) -> Result<Vec<Value>, Error> {
+    let array = json.as_array().ok_or_else(|| path.error("expected an array"))?;
+    if let Some(len) = len.filter(|len| *len != array.len()) {
+        return Err(path.error(format!("expected {} elements, found {}", len, array.len())));
+    }
+    array.iter().zip(types).enumerate()
+        .map(|(i, (json, ty))| path.with_index(i, |path| from_json(json, ty, revision, path)))
+        .collect()
+}
+
+fn from_json(json: &Json, ty: &Type, revision: u32, path: &mut Path) -> Result<Value, Error> {
+    let uint = |max: u128, name: &str| {
+        json_uint(json).filter(|x| *x <= max).map(Value::UInt)
+            .ok_or_else(|| path.error(format!("expected an integer that fits {}", name)))
+    };
+    let int = |min: i128, max: i128, name: &str| {
+        json_int(json).filter(|x| (min..=max).contains(x)).map(Value::Int)
+            .ok_or_else(|| path.error(format!("expected an integer that fits {}", name)))
+    };
+    Ok(match ty {
+        Type::Bool => Value::Bool(json.as_bool().ok_or_else(|| path.error("expected a bool"))?),
+        Type::U8 => uint(u8::MAX.into(), "u8")?,
+        Type::U16 => uint(u16::MAX.into(), "u16")?,
+        Type::U32 => uint(u32::MAX.into(), "u32")?,
+        Type::U64 | Type::VarInt => uint(u64::MAX.into(), "u64")?,
+        Type::U128 => uint(u128::MAX, "u128")?,
+        Type::I8 => int(i8::MIN.into(), i8::MAX.into(), "i8")?,
+        Type::I16 => int(i16::MIN.into(), i16::MAX.into(), "i16")?,
+        Type::I32 => int(i32::MIN.into(), i32::MAX.into(), "i32")?,
+        Type::I64 | Type::Isize => int(i64::MIN.into(), i64::MAX.into(), "i64")?,
+        Type::I128 => int(i128::MIN, i128::MAX, "i128")?,
+        Type::F32 => {
+            let x = json_float(json).ok_or_else(|| path.error("expected a float"))?;
+            if x.is_finite() && !(x as f32).is_finite() {
+                return Err(path.error("float doesn't fit f32"));
+            }
+            Value::F32(x as f32)
+        }
+        Type::F64 => Value::F64(json_float(json).ok_or_else(|| path.error("expected a float"))?),
+        Type::Char => {
+            let mut chars = json.as_str().ok_or_else(|| path.error("expected a string"))?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Value::Char(c),
+                _ => return Err(path.error("expected a single character"))
+            }
+        }
+        Type::String(sb) => {
+            let s = json.as_str().ok_or_else(|| path.error("expected a string"))?;
+            check_len(s.len(), *sb, path)?;
+            Value::String(s.to_owned())
+        }
+        Type::Bytes(sb) => {
+            let array = json.as_array().ok_or_else(|| path.error("expected an array of bytes"))?;
+            check_len(array.len(), *sb, path)?;
+            Value::Bytes(array.iter().enumerate().map(|(i, b)| {
+                b.as_u64().and_then(|b| u8::try_from(b).ok())
+                    .ok_or_else(|| path.with_index(i, |path| path.error("expected a byte")))
+            }).collect::<Result<_, _>>()?)
+        }
+        Type::Array(ty, len) => {
+            Value::List(elements_from_json(json, std::iter::repeat(&**ty), Some(*len), revision, path)?)
+        }
+        Type::List(ty, sb) => {
+            let values = elements_from_json(json, std::iter::repeat(&**ty), None, revision, path)?;
+            check_len(values.len(), *sb, path)?;
+            Value::List(values)
+        }
+        Type::Map(key_ty, value_ty, sb) => {
+            let entries = match json {
+                Json::Object(obj) if matches!(**key_ty, Type::String(_)) => obj.iter().map(|(key, json)| {
+                    let key = from_json(&Json::String(key.clone()), key_ty, revision, path)?;
+                    let value = path.with_key(key_str(&key), |path| from_json(json, value_ty, revision, path))?;
+                    Ok((key, value))
+                }).collect::<Result<Vec<_>, Error>>()?,
+                Json::Array(array) => array.iter().enumerate().map(|(i, entry)| path.with_index(i, |path| {
+                    match entry.as_array().map(Vec::as_slice) {
+                        Some([key, value]) => Ok((
+                            path.with_index(0, |path| from_json(key, key_ty, revision, path))?,
+                            path.with_index(1, |path| from_json(value, value_ty, revision, path))?
+                        )),
+                        _ => Err(path.error("expected a [key, value] pair"))
+                    }
+                })).collect::<Result<Vec<_>, Error>>()?,
+                _ => return Err(path.error("expected an object or an array of [key, value] pairs"))
+            };
+            check_len(entries.len(), *sb, path)?;
+            Value::Map(entries)
+        }
+        Type::Option(ty) => Value::Option(match json {
+            Json::Null => None,
+            json => Some(Box::new(from_json(json, ty, revision, path)?))
+        }),
+        Type::Tuple(types) => {
+            Value::List(elements_from_json(json, types.iter(), Some(types.len()), revision, path)?)
+        }
+        Type::Struct(fields) => {
+            let obj = json.as_object().ok_or_else(|| path.error("expected an object"))?;
+            for key in obj.keys() {
+                if !fields.iter().any(|field| field.name == *key && field.exists_in(revision)) {
+                    return Err(path.with_key(key, |path| path.error(format!("field doesn't exist in revision {}", revision))));
+                }
+            }
+            Value::Struct(fields.iter().filter(|field| field.exists_in(revision)).map(|field| {
+                let json = obj.get(&field.name).ok_or_else(|| path.error(format!("missing field '{}'", field.name)))?;
+                let value = path.with_key(&field.name, |path| from_json(json, &field.ty, revision, path))?;
+                Ok((field.name.clone(), value))
+            }).collect::<Result<_, Error>>()?)
+        }
+        Type::Enum(variants) => {
+            let (name, json) = match json {
+                Json::String(name) => (name, None),
+                Json::Object(obj) if obj.len() == 1 => obj.iter().next().map(|(name, json)| (name, Some(json))).unwrap(),
+                _ => return Err(path.error("expected a variant name or an object with a single variant"))
+            };
+            let variant = variants.iter().find(|variant| variant.name == *name)
+                .ok_or_else(|| path.error(format!("unknown variant '{}'", name)))?;
+            let value = match json {
+                Some(json) => path.with_key(name, |path| from_json(json, &variant.ty, revision, path))?,
+                None if variant.ty == Type::Tuple(Vec::new()) => Value::List(Vec::new()),
+                None => return Err(path.error(format!("variant '{}' needs a value", name)))
+            };
+            Value::Enum(name.clone(), Box::new(value))
+        }
+    })
+}
+
+fn key_str(key: &Value) -> &str {
+    match key {
+        Value::String(s) => s,
+        _ => ""
+    }
+}
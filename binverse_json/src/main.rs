@@ -0,0 +1,79 @@
+use std::{io::{self, Read, Write}, process::ExitCode};
+
+use serde_json::Value as Json;
+
+const USAGE: &str = "\
+Usage:
+  binverse-json decode <layout.json> [--revision <n>]
+  binverse-json encode <layout.json> --revision <n> [--no-header]
+
+decode reads binverse data from stdin and writes JSON to stdout. Without
+--revision, the data has to start with the revision.
+encode reads JSON from stdin and writes binverse data for the revision to
+stdout. The revision is written first unless --no-header is given.";
+
+struct Args {
+    encode: bool,
+    layout: String,
+    revision: Option<u32>,
+    header: bool
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let encode = match args.next().as_deref() {
+        Some("encode") => true,
+        Some("decode") => false,
+        _ => return Err(USAGE.to_owned())
+    };
+    let layout = args.next().ok_or_else(|| USAGE.to_owned())?;
+    let mut parsed = Args { encode, layout, revision: None, header: true };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--revision" => {
+                let revision = args.next().and_then(|r| r.parse().ok())
+                    .ok_or("--revision needs a revision number")?;
+                parsed.revision = Some(revision);
+            }
+            "--no-header" => parsed.header = false,
+            _ => return Err(format!("unknown argument '{}'\n\n{}", arg, USAGE))
+        }
+    }
+    if parsed.encode && parsed.revision.is_none() {
+        return Err("encode needs a --revision".to_owned());
+    }
+    Ok(parsed)
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let layout = std::fs::read_to_string(&args.layout)
+        .map_err(|err| format!("failed to read {}: {}", args.layout, err))?;
+    let layout: Json = serde_json::from_str(&layout)
+        .map_err(|err| format!("invalid layout json: {}", err))?;
+    let ty = binverse_json::parse_layout(&layout).map_err(|err| format!("invalid layout: {}", err))?;
+
+    let mut input = Vec::new();
+    io::stdin().read_to_end(&mut input).map_err(|err| err.to_string())?;
+    let output = if args.encode {
+        let json: Json = serde_json::from_slice(&input).map_err(|err| format!("invalid json: {}", err))?;
+        // parse_args makes sure the revision is set
+        binverse_json::json_to_binverse(&json, &ty, args.revision.unwrap_or_default(), args.header)
+            .map_err(|err| err.to_string())?
+    } else {
+        let json = binverse_json::binverse_to_json(&input, &ty, args.revision).map_err(|err| err.to_string())?;
+        let mut out = serde_json::to_vec_pretty(&json).map_err(|err| err.to_string())?;
+        out.push(b'\n');
+        out
+    };
+    io::stdout().write_all(&output).map_err(|err| err.to_string())
+}
+
+fn main() -> ExitCode {
+    match parse_args().and_then(run) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
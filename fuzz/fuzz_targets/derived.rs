@@ -0,0 +1,37 @@
+#![no_main]
+
+use binverse::{serializable, serialize::Serialize, streams::{Deserializer, Serializer}, varint::VarInt};
+use libfuzzer_sys::fuzz_target;
+
+#[serializable]
+struct Inner {
+    id: VarInt,
+    name: SizeBytes<1, String>,
+}
+
+#[serializable]
+struct Outer {
+    version: u32,
+    items: Added<2, Vec<Inner>>,
+    comment: Removed<3, String>,
+    samples: Added<4, SizeBytes<2, Vec<Option<f64>>>>,
+    flags: (bool, [u8; 4]),
+}
+
+// The data starts with the revision, like data written by binverse::write.
+fuzz_target!(|data: &[u8]| {
+    for strict in [false, true] {
+        let Ok(mut d) = Deserializer::new(data) else { return };
+        if strict {
+            d = d.strict();
+        }
+        let revision = d.revision();
+        if let Ok(outer) = d.deserialize::<Outer>() {
+            let mut s = Serializer::new(Vec::new(), revision).expect("writing the revision failed");
+            outer.serialize(&mut s).expect("deserialized value failed to serialize");
+            let buf = s.finish().expect("flushing failed");
+            let mut d = Deserializer::new(buf.as_slice()).expect("reading the revision failed");
+            d.deserialize::<Outer>().expect("reserialized value failed to deserialize");
+        }
+    }
+});
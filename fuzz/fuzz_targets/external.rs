@@ -0,0 +1,34 @@
+#![no_main]
+
+use arrayvec::{ArrayString, ArrayVec};
+use binverse_fuzz::check;
+use bytes::{Bytes, BytesMut};
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use glam::{DMat4, DQuat, IVec3, Mat3, Quat, UVec2, Vec3A, Vec4};
+use indexmap::{IndexMap, IndexSet};
+use libfuzzer_sys::fuzz_target;
+use smallvec::SmallVec;
+use uuid::Uuid;
+
+type Map = IndexMap<String, u32, std::hash::RandomState>;
+type Set = IndexSet<u16, std::hash::RandomState>;
+
+// The impls for third-party types enabled by features. The first byte
+// selects the type, the rest is the serialized data.
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, data)) = data.split_first() else { return };
+    match selector % 12 {
+        0 => check::<Uuid>(data),
+        1 => check::<(NaiveDate, NaiveTime)>(data),
+        2 => check::<(NaiveDateTime, FixedOffset)>(data),
+        3 => check::<(Bytes, BytesMut)>(data),
+        4 => check::<SmallVec<[String; 2]>>(data),
+        5 => check::<ArrayVec<u16, 3>>(data),
+        6 => check::<ArrayString<5>>(data),
+        7 => check::<Map>(data),
+        8 => check::<Set>(data),
+        9 => check::<(Vec3A, Vec4, IVec3, UVec2)>(data),
+        10 => check::<(Quat, DQuat)>(data),
+        _ => check::<(Mat3, DMat4)>(data),
+    }
+});
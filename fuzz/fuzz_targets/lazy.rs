@@ -0,0 +1,19 @@
+#![no_main]
+
+use binverse::lazy::LazyVec;
+use libfuzzer_sys::fuzz_target;
+
+// Accessing the elements of a lazy collection must not panic, the data is
+// read as both layouts.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(v) = LazyVec::<u32>::fixed(data, 0) {
+        assert!(v.encoded_len() <= data.len());
+        v.iter().for_each(drop);
+        assert!(matches!(v.get(v.len()), Ok(None)));
+    }
+    if let Ok(v) = LazyVec::<(String, Option<u16>)>::indexed(data, 0) {
+        assert!(v.encoded_len() <= data.len());
+        v.iter().for_each(drop);
+        assert!(matches!(v.get(v.len()), Ok(None)));
+    }
+});
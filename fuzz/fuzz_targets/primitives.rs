@@ -0,0 +1,69 @@
+#![no_main]
+
+use std::{collections::{BTreeMap, BTreeSet, HashMap, HashSet}, net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6}, num::*, ops::{Range, RangeInclusive}, path::PathBuf, sync::atomic::*, time::{Duration, SystemTime}};
+
+use binverse::{bounded::{BoundedString, BoundedVec}, serialize::SizeBytes};
+use binverse_fuzz::{check, check_sized};
+use libfuzzer_sys::fuzz_target;
+
+// The first byte selects the type, the rest is the serialized data.
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, data)) = data.split_first() else { return };
+    match selector % 64 {
+        0 => check::<bool>(data),
+        1 => check::<u8>(data),
+        2 => check::<u16>(data),
+        3 => check::<u32>(data),
+        4 => check::<u64>(data),
+        5 => check::<u128>(data),
+        6 => check::<i8>(data),
+        7 => check::<i16>(data),
+        8 => check::<i32>(data),
+        9 => check::<i64>(data),
+        10 => check::<i128>(data),
+        11 => check::<f32>(data),
+        12 => check::<f64>(data),
+        13 => check::<[u16; 5]>(data),
+        14 => check::<[String; 3]>(data),
+        15 => check::<Option<String>>(data),
+        16 => check::<(u8, String, Option<i64>)>(data),
+        17 => check::<String>(data),
+        18 => check::<Vec<u8>>(data),
+        19 => check::<Vec<Vec<String>>>(data),
+        20 => check::<HashMap<u16, String>>(data),
+        21 => check::<HashSet<String>>(data),
+        22 => check::<BTreeMap<String, Vec<u32>>>(data),
+        23 => check::<BTreeSet<i32>>(data),
+        24 => check_sized::<String>(data, SizeBytes::One),
+        25 => check_sized::<Vec<u64>>(data, SizeBytes::Two),
+        26 => check_sized::<String>(data, SizeBytes::Four),
+        27 => check_sized::<Vec<String>>(data, SizeBytes::Eight),
+        28 => check::<usize>(data),
+        29 => check::<isize>(data),
+        30 => check::<char>(data),
+        31 => check::<(u8, u16, u32, u64, i8, i16, i32, i64, bool, char, String, Option<u8>, Vec<u8>)>(data),
+        32 => check::<(u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u16)>(data),
+        33 => check::<(NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize)>(data),
+        34 => check::<(NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize)>(data),
+        35 => check::<(Wrapping<u8>, Wrapping<i64>, Wrapping<u128>)>(data),
+        36 => check::<(Saturating<i8>, Saturating<u32>, Saturating<i128>)>(data),
+        37 => check::<(AtomicBool, AtomicU8, AtomicI8, AtomicU16, AtomicI16)>(data),
+        38 => check::<(AtomicU32, AtomicI32, AtomicU64, AtomicI64, AtomicUsize, AtomicIsize)>(data),
+        39 => check::<Duration>(data),
+        40 => check::<SystemTime>(data),
+        41 => check::<(Ipv4Addr, Ipv6Addr)>(data),
+        42 => check::<IpAddr>(data),
+        43 => check::<(SocketAddrV4, SocketAddrV6)>(data),
+        44 => check::<SocketAddr>(data),
+        45 => check::<PathBuf>(data),
+        46 => check::<Range<u32>>(data),
+        47 => check::<RangeInclusive<i64>>(data),
+        48 => check::<Result<u8, String>>(data),
+        49 => check::<BoundedString<8>>(data),
+        50 => check::<BoundedString<300>>(data),
+        51 => check::<BoundedVec<u16, 4>>(data),
+        52 => check::<BoundedVec<String, 70000>>(data),
+        53 => check::<Option<Result<Duration, IpAddr>>>(data),
+        _ => check::<(NonZeroU32, Wrapping<i16>, Saturating<u8>)>(data),
+    }
+});
@@ -0,0 +1,41 @@
+#![no_main]
+
+use binverse::{serialize::SizeBytes, streams::{Deserializer, Serializer}, value::{Field, Type, Value, Variant}};
+use libfuzzer_sys::fuzz_target;
+
+fn types() -> Vec<Type> {
+    let inner = Type::Struct(vec![
+        Field::new("id", Type::VarInt),
+        Field::new("name", Type::String(SizeBytes::One)),
+        Field::new("tags", Type::List(Box::new(Type::String(SizeBytes::Var)), SizeBytes::Var)).added(2),
+        Field::new("old", Type::Bytes(SizeBytes::Two)).removed(3)
+    ]);
+    vec![
+        Type::Tuple(vec![Type::Bool, Type::U8, Type::I16, Type::U32, Type::I64, Type::U128, Type::Isize, Type::F32, Type::F64, Type::Char]),
+        Type::Array(Box::new(Type::Option(Box::new(Type::I128))), 3),
+        Type::Map(Box::new(Type::String(SizeBytes::Var)), Box::new(Type::List(Box::new(Type::U16), SizeBytes::One)), SizeBytes::Four),
+        Type::Enum(vec![Variant::new("ok", Type::U64), Variant::new("err", Type::String(SizeBytes::Eight))]),
+        Type::List(Box::new(inner), SizeBytes::Var),
+    ]
+}
+
+// The first byte selects the type and the second the revision, the rest is
+// the serialized data. Decoded values have to encode to the same bytes again.
+fuzz_target!(|data: &[u8]| {
+    let [selector, revision, data @ ..] = data else { return };
+    let types = types();
+    let ty = &types[*selector as usize % types.len()];
+    let revision = *revision as u32 % 5;
+    let mut d = Deserializer::new_no_revision(data, revision);
+    if let Ok(value) = Value::decode(&mut d, ty) {
+        let mut s = Serializer::new_no_revision(Vec::new()).for_revision(revision);
+        value.encode(&mut s, ty).expect("decoded value failed to encode");
+        let buf = s.finish().expect("flushing failed");
+        let mut d = Deserializer::new_no_revision(buf.as_slice(), revision);
+        let decoded = Value::decode(&mut d, ty).expect("encoded value failed to decode");
+        // compared as bytes because NaN floats aren't equal to themselves
+        let mut s = Serializer::new_no_revision(Vec::new()).for_revision(revision);
+        decoded.encode(&mut s, ty).expect("decoded value failed to encode");
+        assert_eq!(s.finish().expect("flushing failed"), buf);
+    }
+});
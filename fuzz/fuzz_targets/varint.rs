@@ -0,0 +1,15 @@
+#![no_main]
+
+use binverse::varint::{self, VarInt};
+use binverse_fuzz::check;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    check::<VarInt>(data);
+    if let Ok(x) = varint::read(data) {
+        let mut buf = Vec::new();
+        varint::write(x, &mut buf).expect("writing a varint failed");
+        assert_eq!(varint::read(buf.as_slice()).expect("reading a written varint failed"), x);
+        assert_eq!(buf.len(), varint::len(x));
+    }
+});
@@ -0,0 +1,56 @@
+//! Shared checks for the fuzz targets. Run a target with
+//! `cargo +nightly fuzz run <target>` from the repository root.
+
+use binverse::{serialize::{Deserialize, Serialize, SizeBytes, SizedDeserialize, SizedSerialize}, streams::{Deserializer, Serializer}};
+
+/// Deserializes a value from untrusted data in normal and strict mode. Any
+/// value that was read successfully has to serialize again and the result has
+/// to be readable. Data accepted in strict mode is canonical, so serializing
+/// the value in canonical mode has to reproduce the bytes that were read.
+/// Skipping the value must not panic either.
+pub fn check<T: Serialize + Deserialize>(data: &[u8]) {
+    let _ = Deserializer::new_no_revision(data, 0).skip_value::<T>();
+    for strict in [false, true] {
+        let mut d = Deserializer::new_no_revision(data, 0);
+        if strict {
+            d = d.strict();
+        }
+        if let Ok(t) = d.deserialize::<T>() {
+            let mut s = Serializer::new_no_revision(Vec::new());
+            if strict {
+                s = s.canonical();
+            }
+            t.serialize(&mut s).expect("deserialized value failed to serialize");
+            let buf = s.finish().expect("flushing failed");
+            if strict {
+                assert_eq!(buf, data[..d.bytes_read() as usize], "canonical data changed after reserializing");
+            }
+            Deserializer::new_no_revision(buf.as_slice(), 0).deserialize::<T>()
+                .expect("reserialized value failed to deserialize");
+        }
+    }
+}
+
+/// Like [check], but for a sized data structure using the size bytes.
+pub fn check_sized<T: SizedSerialize + SizedDeserialize>(data: &[u8], sb: SizeBytes) {
+    let _ = Deserializer::new_no_revision(data, 0).skip_sized::<T>(sb);
+    for strict in [false, true] {
+        let mut d = Deserializer::new_no_revision(data, 0);
+        if strict {
+            d = d.strict();
+        }
+        if let Ok(t) = d.deserialize_sized::<T>(sb) {
+            let mut s = Serializer::new_no_revision(Vec::new());
+            if strict {
+                s = s.canonical();
+            }
+            s.serialize_sized(sb, &t).expect("deserialized value failed to serialize");
+            let buf = s.finish().expect("flushing failed");
+            if strict {
+                assert_eq!(buf, data[..d.bytes_read() as usize], "canonical data changed after reserializing");
+            }
+            Deserializer::new_no_revision(buf.as_slice(), 0).deserialize_sized::<T>(sb)
+                .expect("reserialized value failed to deserialize");
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Array deserialization has to drop the elements read before an error or a
+//! panic exactly once. Run with `cargo +nightly miri test --test arrays` to
+//! also check for undefined behaviour.
+
+use std::{cell::Cell, panic::{self, AssertUnwindSafe}};
+
+use binverse::{error::{BinverseError, BinverseResult}, serialize::Deserialize, streams::{Decoder, Deserializer}};
+
+thread_local! {
+    static ALIVE: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts live instances. Deserializing the byte 0xfe returns an error and
+/// 0xff panics.
+#[derive(Debug)]
+struct Tracked(#[allow(dead_code)] Box<u8>);
+
+impl Deserialize for Tracked {
+    fn deserialize<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        match d.deserialize::<u8>()? {
+            0xfe => Err(BinverseError::InvalidData),
+            0xff => panic!("element panicked"),
+            x => {
+                ALIVE.with(|a| a.set(a.get() + 1));
+                Ok(Self(Box::new(x)))
+            }
+        }
+    }
+}
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        ALIVE.with(|a| a.set(a.get() - 1));
+    }
+}
+
+fn read<T: Deserialize>(data: &[u8]) -> BinverseResult<T> {
+    Deserializer::new_no_revision(data, 0).deserialize()
+}
+
+fn alive() -> usize { ALIVE.with(Cell::get) }
+
+#[test]
+fn complete() {
+    let arr: [Tracked; 4] = read(&[1, 2, 3, 4]).unwrap();
+    assert_eq!(alive(), 4);
+    drop(arr);
+    assert_eq!(alive(), 0);
+
+    assert_eq!(read::<[bool; 3]>(&[1, 0, 1]).unwrap(), [true, false, true]);
+    assert!(read::<[bool; 3]>(&[1, 2, 1]).is_err());
+    assert_eq!(read::<[String; 2]>(&[1, b'a', 0]).unwrap(), ["a".to_owned(), String::new()]);
+    assert_eq!(read::<[u32; 0]>(&[]).unwrap(), []);
+}
+
+#[test]
+fn error_partway() {
+    assert!(matches!(read::<[Tracked; 4]>(&[1, 2, 0xfe, 4]), Err(BinverseError::InvalidData)));
+    assert_eq!(alive(), 0);
+    // the data ends after two elements
+    assert!(read::<[Tracked; 4]>(&[1, 2]).is_err());
+    assert_eq!(alive(), 0);
+    assert!(read::<[[Tracked; 2]; 2]>(&[1, 2, 3, 0xfe]).is_err());
+    assert_eq!(alive(), 0);
+}
+
+#[test]
+fn panic_partway() {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| read::<[Tracked; 4]>(&[1, 2, 0xff, 4])));
+    assert!(result.is_err());
+    assert_eq!(alive(), 0);
+}
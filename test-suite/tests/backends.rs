@@ -0,0 +1,130 @@
+use std::collections::{BTreeSet, HashMap};
+
+use binverse::{error::{BinverseError, BinverseResult}, serializable, serialize::{Deserialize, Serialize}, streams::{Decoder, Deserializer, Encoder, Serializer}};
+
+#[serializable(projection)]
+#[derive(Debug, PartialEq)]
+struct Record {
+    id: u32,
+    name: String,
+    scores: Vec<i16>,
+    removed: Removed<2, Added<1, u64>>,
+    tags: BTreeSet<String>,
+    ratio: f64,
+    counts: Added<1, HashMap<String, usize>>
+}
+
+fn record() -> Record {
+    Record {
+        id: 0x0102_0304,
+        name: "backend".to_owned(),
+        scores: vec![-1, 300],
+        tags: ["b".to_owned(), "a".to_owned()].into(),
+        ratio: 0.25,
+        counts: [("x".to_owned(), 1000)].into()
+    }
+}
+
+/// Only counts the bytes instead of writing them.
+struct Counter {
+    len: u64
+}
+impl Encoder for Counter {
+    fn write(&mut self, buf: &[u8]) -> BinverseResult<()> {
+        self.len += buf.len() as u64;
+        Ok(())
+    }
+    fn revision(&self) -> u32 { 3 }
+}
+
+/// Writes numbers in big endian byte order and lengths as u32.
+struct BigEndian {
+    data: Vec<u8>
+}
+impl Encoder for BigEndian {
+    fn write(&mut self, buf: &[u8]) -> BinverseResult<()> {
+        self.data.extend_from_slice(buf);
+        Ok(())
+    }
+    fn revision(&self) -> u32 { 3 }
+    fn write_u32(&mut self, x: u32) -> BinverseResult<()> { self.write(&x.to_be_bytes()) }
+    fn write_i16(&mut self, x: i16) -> BinverseResult<()> { self.write(&x.to_be_bytes()) }
+    fn write_f64(&mut self, x: f64) -> BinverseResult<()> { self.write(&x.to_be_bytes()) }
+    fn write_varint(&mut self, x: u64) -> BinverseResult<()> {
+        self.write_u32(u32::try_from(x).map_err(|_| BinverseError::IntegerOverflow)?)
+    }
+}
+
+/// Reads data written by [BigEndian].
+struct BigEndianReader<'a> {
+    data: &'a [u8]
+}
+impl Decoder for BigEndianReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> BinverseResult<()> {
+        if self.data.len() < buf.len() {
+            return Err(BinverseError::InvalidData);
+        }
+        let (read, rest) = self.data.split_at(buf.len());
+        buf.copy_from_slice(read);
+        self.data = rest;
+        Ok(())
+    }
+    fn revision(&self) -> u32 { 3 }
+    fn read_u32(&mut self) -> BinverseResult<u32> {
+        let mut b = [0; 4];
+        self.read(&mut b)?;
+        Ok(u32::from_be_bytes(b))
+    }
+    fn read_i16(&mut self) -> BinverseResult<i16> {
+        let mut b = [0; 2];
+        self.read(&mut b)?;
+        Ok(i16::from_be_bytes(b))
+    }
+    fn read_f64(&mut self) -> BinverseResult<f64> {
+        let mut b = [0; 8];
+        self.read(&mut b)?;
+        Ok(f64::from_be_bytes(b))
+    }
+    fn read_varint(&mut self) -> BinverseResult<u64> {
+        self.read_u32().map(u64::from)
+    }
+}
+
+#[test]
+fn counting_encoder() {
+    let mut counter = Counter { len: 0 };
+    record().serialize(&mut counter).unwrap();
+    assert_eq!(counter.len, binverse::serialized_size_no_revision(&record()).unwrap());
+}
+
+#[test]
+fn custom_wire_format() {
+    let mut s = BigEndian { data: Vec::new() };
+    record().serialize(&mut s).unwrap();
+    assert_eq!(s.data[..4], [1, 2, 3, 4]);
+    // the name length is written as a big endian u32 instead of a varint
+    assert_eq!(s.data[4..8], [0, 0, 0, 7]);
+
+    let mut d = BigEndianReader { data: &s.data };
+    assert_eq!(Record::deserialize(&mut d).unwrap(), record());
+    assert!(d.data.is_empty());
+
+    let mut d = BigEndianReader { data: &s.data };
+    let projection = Record::project(&mut d, &["scores"]).unwrap();
+    assert_eq!(projection.scores, Some(vec![-1, 300]));
+    assert!(d.data.is_empty());
+}
+
+#[test]
+fn default_backend_unchanged() {
+    let mut s = Serializer::new(Vec::new(), 3).unwrap();
+    record().serialize(&mut s).unwrap();
+    let data = s.finish().unwrap();
+    assert_eq!(data[4..8], 0x0102_0304_u32.to_le_bytes());
+
+    // the Decoder methods are available on the Deserializer
+    let mut d = Deserializer::new(data.as_slice()).unwrap();
+    assert_eq!(Decoder::deserialize::<Record>(&mut d).unwrap(), record());
+    let mut d = Deserializer::new(data.as_slice()).unwrap();
+    assert_eq!(d.deserialize::<Record>().unwrap(), record());
+}
@@ -0,0 +1,61 @@
+use binverse::{bounded::{BoundedString, BoundedVec}, error::BinverseError, serializable, serialize::SizeBytes};
+
+#[test]
+fn bounded_string() {
+    let mut s = BoundedString::<8>::try_from("binverse").unwrap();
+    assert!(matches!(s.push('!'), Err(BinverseError::CapacityExceeded { capacity: 8, found: 9 })));
+    assert_eq!(s.as_str(), "binverse");
+    assert!(BoundedString::<8>::try_from("too long!".to_owned()).is_err());
+    s.pop();
+    s.push_str("ä").unwrap_err();
+    s.push('!').unwrap();
+    assert_eq!(&*s, "binvers!");
+
+    assert!(matches!(BoundedString::<255>::SIZE_BYTES, SizeBytes::One));
+    assert!(matches!(BoundedString::<256>::SIZE_BYTES, SizeBytes::Two));
+    assert!(matches!(BoundedString::<70000>::SIZE_BYTES, SizeBytes::Four));
+
+    let bytes = binverse::write_no_revision(Vec::new(), s.clone()).unwrap();
+    assert_eq!(bytes.len(), 1 + 8);
+    let (read, _) = binverse::read_no_revision::<_, BoundedString<8>>(bytes.as_slice(), 0).unwrap();
+    assert_eq!(read, s);
+
+    // the length is checked before reading the string
+    match binverse::read_no_revision::<_, BoundedString<4>>(bytes.as_slice(), 0) {
+        Err(BinverseError::CapacityExceeded { capacity: 4, found: 8 }) => (),
+        other => panic!("Expected capacity error, got {:?}", other)
+    }
+}
+
+#[test]
+fn bounded_vec() {
+    #[serializable]
+    #[derive(Debug, PartialEq)]
+    struct Inventory {
+        owner: BoundedString<32>,
+        items: BoundedVec<u16, 1000>
+    }
+    let mut items = BoundedVec::new();
+    for i in 0..1000 {
+        items.push(i).unwrap();
+    }
+    assert!(items.push(1000).is_err());
+    items[0] = 5;
+    let inventory = Inventory {
+        owner: BoundedString::try_from("player").unwrap(),
+        items
+    };
+    let bytes = binverse::write_no_revision(Vec::new(), inventory).unwrap();
+    assert_eq!(bytes.len(), 1 + 6 + 2 + 2000);
+    let (read, _) = binverse::read_no_revision::<_, Inventory>(bytes.as_slice(), 0).unwrap();
+    assert_eq!(read.items.len(), 1000);
+    assert_eq!(read.items[..2], [5, 1]);
+
+    assert!(BoundedVec::<u8, 2>::try_from(vec![1, 2, 3]).is_err());
+    // a huge length is rejected without allocating
+    let huge = [0xFF, 0xFF, 0xFF, 0xFF];
+    assert!(matches!(
+        binverse::read_no_revision::<_, BoundedVec<u64, 100_000>>(huge.as_slice(), 0),
+        Err(BinverseError::CapacityExceeded { capacity: 100_000, found: 0xFFFF_FFFF })
+    ));
+}
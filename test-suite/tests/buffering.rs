@@ -0,0 +1,135 @@
+use std::io::{self, Read, Write};
+
+use binverse::{error::BinverseError, serialize::Serialize, streams::{Deserializer, Serializer}};
+
+/// Counts the calls to the writer and optionally fails when flushing.
+#[derive(Default)]
+struct CountingWriter {
+    data: Vec<u8>,
+    writes: usize,
+    flushes: usize,
+    fail_flush: bool,
+    /// The number of bytes accepted before a single write fails.
+    fail_write_after: Option<usize>
+}
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writes += 1;
+        if let Some(n) = self.fail_write_after {
+            if n == 0 {
+                self.fail_write_after = None;
+                return Err(io::Error::other("write failed"));
+            }
+            let len = buf.len().min(n);
+            self.fail_write_after = Some(n - len);
+            self.data.extend_from_slice(&buf[..len]);
+            return Ok(len);
+        }
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushes += 1;
+        if self.fail_flush {
+            return Err(io::Error::other("flush failed"));
+        }
+        Ok(())
+    }
+}
+
+/// Counts the calls to the reader and returns at most 100 bytes per call.
+struct CountingReader<'a> {
+    data: &'a [u8],
+    reads: usize
+}
+impl Read for CountingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reads += 1;
+        let n = buf.len().min(self.data.len()).min(100);
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+fn write_values(s: &mut Serializer<CountingWriter>) {
+    for i in 0..1000_u32 {
+        i.serialize(s).unwrap();
+    }
+    vec![7_u8; 500].serialize(s).unwrap();
+}
+
+#[test]
+fn buffered_serializer() {
+    let mut unbuffered = Serializer::new(CountingWriter::default(), 1).unwrap();
+    write_values(&mut unbuffered);
+    let unbuffered = unbuffered.finish().unwrap();
+
+    let mut s = Serializer::new(CountingWriter::default(), 1).unwrap().buffered(256);
+    write_values(&mut s);
+    assert_eq!(s.bytes_written(), unbuffered.data.len() as u64);
+    s.flush().unwrap();
+    assert_eq!(s.w.flushes, 1);
+    let buffered = s.finish().unwrap();
+
+    assert_eq!(buffered.data, unbuffered.data);
+    assert_eq!(buffered.flushes, 2);
+    assert!(unbuffered.writes > 1500);
+    assert!(buffered.writes < 30, "{} writes", buffered.writes);
+}
+
+#[test]
+fn flush_errors() {
+    let w = CountingWriter { fail_flush: true, ..Default::default() };
+    let mut s = Serializer::new_no_revision(w).buffered(64);
+    5_u64.serialize(&mut s).unwrap();
+    assert!(matches!(s.finish(), Err(BinverseError::IO(_))));
+}
+
+#[test]
+fn write_errors() {
+    // the writer accepts a part of the buffer before failing once
+    let w = CountingWriter { fail_write_after: Some(3), ..Default::default() };
+    let mut s = Serializer::new_no_revision(w).buffered(8);
+    s.write(&[1, 2, 3, 4, 5, 6]).unwrap();
+    assert!(matches!(s.write(&[0; 4]), Err(BinverseError::IO(_))));
+    // the buffered bytes aren't written a second time
+    s.write(&[7, 8]).unwrap();
+    s.flush().unwrap();
+    assert_eq!(s.w.data, [1, 2, 3, 7, 8]);
+
+    s.write(&[9]).unwrap();
+    s.w.fail_write_after = Some(0);
+    assert!(matches!(s.flush(), Err(BinverseError::IO(_))));
+    assert_eq!(s.finish().unwrap().data, [1, 2, 3, 7, 8]);
+}
+
+#[test]
+fn buffered_deserializer() {
+    let mut s = Serializer::new(Vec::new(), 3).unwrap();
+    for i in 0..1000_u32 {
+        i.serialize(&mut s).unwrap();
+    }
+    vec![1_u8; 2000].serialize(&mut s).unwrap();
+    let mut data = s.finish().unwrap();
+    data.extend_from_slice(b"rest");
+
+    let mut d = Deserializer::new(CountingReader { data: &data, reads: 0 }).unwrap().buffered(512);
+    assert_eq!(d.revision(), 3);
+    for i in 0..1000_u32 {
+        assert_eq!(d.deserialize::<u32>().unwrap(), i);
+    }
+    assert_eq!(d.deserialize::<Vec<u8>>().unwrap(), vec![1; 2000]);
+    assert_eq!(d.bytes_read(), data.len() as u64 - 4);
+    // the reader returns at most 100 bytes per call
+    assert!(d.r.reads < 70, "{} reads", d.r.reads);
+    assert_eq!(d.buffered_bytes(), b"rest");
+    assert!(d.deserialize::<u64>().is_err());
+
+    // large reads bypass the buffer
+    let mut d = Deserializer::new_no_revision(data.as_slice(), 0).buffered(16);
+    let mut buf = [0; 4004];
+    d.read(&mut buf).unwrap();
+    assert_eq!(buf, data[..4004]);
+    assert_eq!(d.buffered_bytes(), &[] as &[u8]);
+}
@@ -0,0 +1,57 @@
+use std::collections::{HashMap, HashSet};
+
+use binverse::{error::BinverseError, serialize::{Deserialize, Serialize}, streams::{Deserializer, Serializer}};
+
+fn canonical_bytes<T: Serialize>(t: &T) -> Vec<u8> {
+    let mut s = Serializer::new_no_revision(Vec::new()).canonical();
+    t.serialize(&mut s).unwrap();
+    s.finish().unwrap()
+}
+
+fn strict_read<T: Deserialize>(bytes: &[u8]) -> Result<T, BinverseError> {
+    Deserializer::new_no_revision(bytes, 0).strict().deserialize()
+}
+
+#[test]
+fn sorted_maps() {
+    // maps with the same content but a different insertion order and capacity
+    let a: HashMap<String, u32> = (0..100).map(|i| (format!("key {}", i), i)).collect();
+    let mut b = HashMap::with_capacity(1000);
+    for i in (0..100).rev() {
+        b.insert(format!("key {}", i), i);
+    }
+    let bytes = canonical_bytes(&a);
+    assert_eq!(bytes, canonical_bytes(&b));
+    assert_eq!(strict_read::<HashMap<String, u32>>(&bytes).unwrap(), a);
+
+    let set: HashSet<u64> = (0..100).map(|i| i * 7919 % 1000).collect();
+    let bytes = canonical_bytes(&set);
+    assert_eq!(strict_read::<HashSet<u64>>(&bytes).unwrap(), set);
+
+    // unsorted or duplicate keys are rejected in strict mode
+    let unsorted = [2_u8, 2, 1];
+    assert!(matches!(strict_read::<HashSet<u8>>(&unsorted), Err(BinverseError::NonCanonical)));
+    assert_eq!(Deserializer::new_no_revision(unsorted.as_slice(), 0).deserialize::<HashSet<u8>>().unwrap().len(), 2);
+    assert!(matches!(strict_read::<HashSet<u8>>(&[2, 1, 1]), Err(BinverseError::NonCanonical)));
+}
+
+#[test]
+fn canonical_floats() {
+    let nan = f64::from_bits(0xfff8_0000_0000_1234);
+    assert!(nan.is_nan());
+    let bytes = canonical_bytes(&nan);
+    assert_eq!(bytes, 0x7ff8_0000_0000_0000_u64.to_le_bytes());
+    assert!(strict_read::<f64>(&bytes).unwrap().is_nan());
+    assert!(matches!(strict_read::<f64>(&nan.to_le_bytes()), Err(BinverseError::NonCanonical)));
+    assert!(matches!(strict_read::<f32>(&f32::from_bits(0x7fc0_0001).to_le_bytes()), Err(BinverseError::NonCanonical)));
+    assert_eq!(strict_read::<f32>(&1.5_f32.to_le_bytes()).unwrap(), 1.5);
+}
+
+#[test]
+fn overlong_varints() {
+    assert_eq!(strict_read::<String>(&[1, b'a']).unwrap(), "a");
+    // the length 1 is encoded with two bytes
+    let overlong = [0x81, 0x00, b'a'];
+    assert!(matches!(strict_read::<String>(&overlong), Err(BinverseError::NonCanonical)));
+    assert_eq!(Deserializer::new_no_revision(overlong.as_slice(), 0).deserialize::<String>().unwrap(), "a");
+}
@@ -0,0 +1,64 @@
+use binverse::{error::BinverseError, serialize::{Serialize, SizeBytes}, streams::{Deserializer, Serializer}, varint::VarInt};
+
+#[test]
+fn byte_counts() {
+    let mut s = Serializer::new(Vec::new(), 0).unwrap();
+    assert_eq!(s.bytes_written(), 4);
+    5_u16.serialize(&mut s).unwrap();
+    VarInt(300).serialize(&mut s).unwrap();
+    s.serialize_sized(SizeBytes::Var, &"Hello".to_owned()).unwrap();
+    assert_eq!(s.bytes_written(), 4 + 2 + 2 + 1 + 5);
+    let data = s.finish().unwrap();
+    assert_eq!(data.len(), 14);
+
+    let mut d = Deserializer::new(data.as_slice()).unwrap();
+    assert_eq!(d.bytes_read(), 4);
+    let _: u16 = d.deserialize().unwrap();
+    let _: VarInt = d.deserialize().unwrap();
+    assert_eq!(d.bytes_read(), 8);
+    let _: String = d.deserialize().unwrap();
+    assert_eq!(d.bytes_read(), 14);
+}
+
+#[test]
+fn declared_lengths() {
+    // a sub-object is written with its length in front so it can be skipped
+    let sub = {
+        let mut s = Serializer::new_no_revision(Vec::new());
+        let cp = s.checkpoint();
+        (1_u32, "sub-object".to_owned()).serialize(&mut s).unwrap();
+        assert_eq!(s.since(cp).unwrap(), 4 + 1 + 10);
+        s.finish().unwrap()
+    };
+    let mut s = Serializer::new_no_revision(Vec::new());
+    s.serialize_sized(SizeBytes::One, &sub).unwrap();
+    7_u8.serialize(&mut s).unwrap();
+    let data = s.finish().unwrap();
+
+    // the full sub-object is read
+    let mut d = Deserializer::new_no_revision(data.as_slice(), 0);
+    let len: u8 = d.deserialize().unwrap();
+    let cp = d.checkpoint();
+    let _: (u32, String) = d.deserialize().unwrap();
+    d.verify_len(cp, len as u64).unwrap();
+    assert_eq!(d.deserialize::<u8>().unwrap(), 7);
+
+    // only the first field is read, the rest is skipped
+    let mut d = Deserializer::new_no_revision(data.as_slice(), 0);
+    let len: u8 = d.deserialize().unwrap();
+    let cp = d.checkpoint();
+    assert_eq!(d.deserialize::<u32>().unwrap(), 1);
+    match d.verify_len(cp, len as u64) {
+        Err(BinverseError::LengthMismatch { expected: 15, found: 4 }) => (),
+        other => panic!("Expected length mismatch, got {:?}", other)
+    }
+    d.skip_to(cp, len as u64).unwrap();
+    assert_eq!(d.deserialize::<u8>().unwrap(), 7);
+    assert!(matches!(d.skip_to(cp, 2), Err(BinverseError::LengthMismatch { expected: 2, found: 16 })));
+    assert!(matches!(d.skip(1), Err(BinverseError::IO(_))));
+
+    // a checkpoint of a deserializer that is further ahead
+    let fresh = Deserializer::new_no_revision(data.as_slice(), 0);
+    assert!(matches!(fresh.since(cp), Err(BinverseError::InvalidCheckpoint { offset: 1, position: 0 })));
+    assert!(matches!(fresh.verify_len(cp, 0), Err(BinverseError::InvalidCheckpoint { .. })));
+}
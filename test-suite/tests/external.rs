@@ -0,0 +1,89 @@
+use arrayvec::{ArrayString, ArrayVec};
+use binverse::{error::BinverseError, serializable, serialize::{Deserialize, Serialize}, streams::{Deserializer, Serializer}, testing::assert_roundtrip};
+use bytes::{Bytes, BytesMut};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, Utc};
+use glam::{IVec3, Mat4, Quat, Vec2};
+use indexmap::{IndexMap, IndexSet};
+use smallvec::{smallvec, SmallVec};
+use uuid::Uuid;
+
+fn bytes<T: Serialize>(t: &T) -> Vec<u8> {
+    let mut s = Serializer::new_no_revision(Vec::new());
+    t.serialize(&mut s).unwrap();
+    s.finish().unwrap()
+}
+
+fn read<T: Deserialize>(data: &[u8]) -> Result<T, BinverseError> {
+    Deserializer::new_no_revision(data, 0).deserialize()
+}
+
+#[test]
+fn values() {
+    let id = Uuid::from_u128(0x1234_5678_9abc_def0_1234_5678_9abc_def0);
+    assert_roundtrip(&id);
+    assert_eq!(bytes(&id), id.as_bytes());
+
+    let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+    assert_roundtrip(&date);
+    assert_roundtrip(&NaiveTime::from_hms_nano_opt(23, 59, 59, 1_500_000_000).unwrap());
+    assert_roundtrip(&date.and_hms_opt(12, 0, 0).unwrap().and_utc());
+    let local: DateTime<FixedOffset> = DateTime::parse_from_rfc3339("2024-02-29T12:00:00+05:30").unwrap();
+    assert_roundtrip(&local);
+    assert!(read::<NaiveTime>(&bytes(&(86_400_u32, 0_u32))).is_err());
+    assert!(read::<DateTime<Utc>>(&bytes(&(i32::MAX, 0_u32, 0_u32))).is_err());
+
+    assert_roundtrip(&Vec2::new(1.0, -2.5));
+    assert_roundtrip(&IVec3::new(1, 2, 3));
+    assert_roundtrip(&Quat::from_rotation_z(1.0));
+    assert_roundtrip(&Mat4::from_cols_array(&core::array::from_fn(|i| i as f32)));
+    assert_eq!(bytes(&Vec2::new(1.0, 2.0)), bytes(&[1.0_f32, 2.0]));
+}
+
+#[test]
+fn collections() {
+    assert_roundtrip(&Bytes::from_static(b"binverse"));
+    assert_roundtrip(&BytesMut::from(&b"abc"[..]));
+    assert_eq!(bytes(&Bytes::from_static(b"ab")), bytes(&vec![b'a', b'b']));
+
+    let small: SmallVec<[u16; 4]> = smallvec![1, 2, 3, 4, 5];
+    assert_roundtrip(&small);
+
+    let arr: ArrayVec<u32, 3> = [1, 2, 3].into();
+    assert_roundtrip(&arr);
+    assert_roundtrip(&ArrayString::<8>::from("hello").unwrap());
+    assert!(matches!(read::<ArrayVec<u32, 2>>(&bytes(&arr)), Err(BinverseError::CapacityExceeded { capacity: 2, found: 3 })));
+    assert!(matches!(read::<ArrayString<4>>(&bytes(&"hello")), Err(BinverseError::CapacityExceeded { capacity: 4, found: 5 })));
+
+    let map: IndexMap<String, u8> = [("z".to_owned(), 1), ("a".to_owned(), 2)].into_iter().collect();
+    assert_roundtrip(&map);
+    // the order is kept, even in canonical mode
+    let mut s = Serializer::new_no_revision(Vec::new()).canonical();
+    map.serialize(&mut s).unwrap();
+    assert_eq!(s.finish().unwrap(), bytes(&vec![("z".to_owned(), 1_u8), ("a".to_owned(), 2)]));
+    let set: IndexSet<u8> = [3, 1, 2].into_iter().collect();
+    assert_roundtrip(&set);
+    assert!(matches!(read::<IndexSet<u8>>(&[2, 1, 1]), Err(BinverseError::InvalidData)));
+}
+
+#[test]
+fn sized_fields() {
+    #[serializable]
+    #[derive(Debug, PartialEq)]
+    struct Packet {
+        id: Uuid,
+        payload: SizeBytes<2, Bytes>,
+        tags: SizeBytes<1, SmallVec<[u8; 8]>>,
+        name: SizeBytes<1, ArrayString<16>>,
+        fields: SizeBytes<1, IndexMap<u8, String>>
+    }
+
+    let packet = Packet {
+        id: Uuid::nil(),
+        payload: Bytes::from_static(&[0; 300]),
+        tags: smallvec![1, 2],
+        name: ArrayString::from("p").unwrap(),
+        fields: [(1, "x".to_owned())].into_iter().collect()
+    };
+    assert_roundtrip(&packet);
+    assert_eq!(bytes(&packet).len(), 16 + 2 + 300 + 1 + 2 + 1 + 1 + 1 + 1 + 1 + 1);
+}
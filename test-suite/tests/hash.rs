@@ -0,0 +1,81 @@
+use std::{collections::{hash_map::DefaultHasher, HashMap, HashSet}, hash::Hasher};
+
+use binverse::{serializable, streams::Serializer};
+
+/// 64-bit FNV-1a, a hasher with a fixed algorithm.
+struct Fnv(u64);
+impl Default for Fnv {
+    fn default() -> Self { Self(0xcbf2_9ce4_8422_2325) }
+}
+impl Hasher for Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = (self.0 ^ *b as u64).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+    fn finish(&self) -> u64 { self.0 }
+}
+
+fn fnv(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+struct Document {
+    title: String,
+    words: HashMap<String, u32>,
+    tags: HashSet<String>,
+    score: Added<2, f32>
+}
+
+fn document(order: impl Iterator<Item = u32> + Clone) -> Document {
+    Document {
+        title: "hashing".to_owned(),
+        words: order.clone().map(|i| (format!("word {}", i), i)).collect(),
+        tags: order.map(|i| format!("tag {}", i % 7)).collect(),
+        score: f32::NAN
+    }
+}
+
+#[test]
+fn equal_values_hash_equally() {
+    // every map gets its own random iteration order
+    let a = document(0..50);
+    let b = document((0..50).rev());
+    assert_eq!(binverse::hash::<Fnv>(&a).unwrap(), binverse::hash::<Fnv>(&b).unwrap());
+    assert_ne!(binverse::hash::<Fnv>(&a).unwrap(), binverse::hash::<Fnv>(&document(0..49)).unwrap());
+
+    // any Hasher can be used
+    let map: HashMap<u64, i16> = (0..20).map(|i| (i * 31, -(i as i16))).collect();
+    let copy: HashMap<u64, i16> = (0..20).rev().map(|i| (i * 31, -(i as i16))).collect();
+    assert_eq!(binverse::hash::<DefaultHasher>(&map).unwrap(), binverse::hash::<DefaultHasher>(&copy).unwrap());
+}
+
+#[test]
+fn stable_hash() {
+    // the hash only depends on the canonical encoding, so it stays the same
+    // across runs and platforms
+    let map: HashMap<String, u8> = [("b".to_owned(), 2), ("a".to_owned(), 1)].into();
+    let encoding = [2, 1, b'a', 1, 1, b'b', 2];
+    assert_eq!(binverse::hash::<Fnv>(&map).unwrap(), fnv(&encoding));
+    assert_eq!(binverse::hash::<Fnv>(&map).unwrap(), 0x5c26_9d07_95b0_4059);
+
+    let doc = document(0..50);
+    let mut s = Serializer::new_no_revision(Vec::new()).canonical();
+    binverse::serialize::Serialize::serialize(&doc, &mut s).unwrap();
+    let bytes = s.finish().unwrap();
+    assert_eq!(binverse::write_canonical(Vec::new(), &doc).unwrap(), bytes);
+    assert_eq!(binverse::hash::<Fnv>(&doc).unwrap(), fnv(&bytes));
+}
+
+#[test]
+fn hash_into() {
+    let mut hasher = Fnv::default();
+    binverse::hash_into(&mut hasher, &1_u32).unwrap();
+    binverse::hash_into(&mut hasher, &"text").unwrap();
+    assert_eq!(hasher.finish(), fnv(&[1, 0, 0, 0, 4, b't', b'e', b'x', b't']));
+    assert_eq!(hasher.finish(), binverse::hash::<Fnv>(&(1_u32, "text")).unwrap());
+}
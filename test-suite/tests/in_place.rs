@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use binverse::{serializable, serialize::{Deserialize, Serialize}, streams::{Deserializer, Serializer}};
+
+fn write<T: Serialize>(value: &T, revision: u32) -> Vec<u8> {
+    let mut s = Serializer::new(Vec::new(), revision).unwrap();
+    value.serialize(&mut s).unwrap();
+    s.finish().unwrap()
+}
+
+fn read_in_place<T: Deserialize>(data: &[u8], value: &mut T) {
+    let mut d = Deserializer::new(data).unwrap();
+    d.deserialize_in_place(value).unwrap();
+    assert!(d.finish().is_empty());
+}
+
+#[test]
+fn reuse_allocations() {
+    let mut names = vec!["a".repeat(100), "b".repeat(100), "c".repeat(100)];
+    let ptrs: Vec<_> = names.iter().map(|name| name.as_ptr()).collect();
+    let vec_ptr = names.as_ptr();
+
+    read_in_place(&write(&vec!["x".to_owned(), "yz".to_owned()], 0), &mut names);
+    assert_eq!(names, ["x", "yz"]);
+    assert_eq!(names.as_ptr(), vec_ptr);
+    assert_eq!(names.iter().map(|name| name.as_ptr()).collect::<Vec<_>>(), ptrs[..2]);
+
+    read_in_place(&write(&vec!["1".to_owned(); 4], 0), &mut names);
+    assert_eq!(names, ["1"; 4]);
+    assert_eq!(names[..2].iter().map(|name| name.as_ptr()).collect::<Vec<_>>(), ptrs[..2]);
+
+    let mut map: HashMap<u8, Vec<u32>> = (0..100).map(|i| (i, vec![0; 50])).collect();
+    let capacity = map.capacity();
+    let mut ptrs: Vec<_> = map.values().map(|v| v.as_ptr()).collect();
+    read_in_place(&write(&HashMap::from([(1_u8, vec![7_u32]), (200, vec![])]), 0), &mut map);
+    assert_eq!(map, HashMap::from([(1, vec![7]), (200, vec![])]));
+    // the table and the allocations of the values are reused
+    assert_eq!(map.capacity(), capacity);
+    let mut new_ptrs: Vec<_> = map.values().map(|v| v.as_ptr()).collect();
+    ptrs.sort();
+    new_ptrs.sort();
+    assert!(new_ptrs.iter().all(|ptr| ptrs.binary_search(ptr).is_ok()));
+
+    let mut option = Some("option".to_owned());
+    let ptr = option.as_ref().unwrap().as_ptr();
+    read_in_place(&write(&Some("new".to_owned()), 0), &mut option);
+    assert_eq!((option.as_deref(), option.as_ref().unwrap().as_ptr()), (Some("new"), ptr));
+    read_in_place(&write(&None::<String>, 0), &mut option);
+    assert_eq!(option, None);
+}
+
+#[serializable]
+#[derive(Debug, PartialEq, Clone, Default)]
+struct Entity {
+    id: u32,
+    name: SizeBytes<1, String>,
+    old: Removed<2, Vec<u8>>,
+    position: [f32; 3],
+    children: Vec<Entity>,
+    health: Added<1, Option<u16>>
+}
+
+#[test]
+fn derived_structs() {
+    let child = Entity { id: 2, name: "child".to_owned(), position: [1.0; 3], ..Default::default() };
+    let entity = Entity { id: 1, name: "root".to_owned(), children: vec![child.clone(), child], health: Some(10), ..Default::default() };
+
+    let mut state = Entity { id: 9, name: "previous".repeat(10), children: vec![Entity::default()], health: Some(3), ..Default::default() };
+    let name_ptr = state.name.as_ptr();
+    for revision in [2, 3] {
+        read_in_place(&write(&entity, revision), &mut state);
+        assert_eq!(state, entity);
+        assert_eq!(state.name.as_ptr(), name_ptr);
+    }
+
+    // data of revision 0 contains the removed field and doesn't contain `health`
+    #[serializable]
+    struct V0 {
+        id: u32,
+        name: SizeBytes<1, String>,
+        old: Vec<u8>,
+        position: [f32; 3],
+        children: Vec<Entity>
+    }
+    let data = write(&V0 { id: 3, name: "v0".to_owned(), old: vec![1, 2], position: [0.5; 3], children: Vec::new() }, 0);
+    let full: Entity = Deserializer::new(data.as_slice()).unwrap().deserialize().unwrap();
+    read_in_place(&data, &mut state);
+    assert_eq!(state, full);
+    assert_eq!(state.health, None);
+}
+
+#[test]
+fn errors() {
+    // invalid UTF-8 leaves the string empty
+    let mut s = "text".to_owned();
+    let data = write(&vec![0xff_u8, 0xfe], 0);
+    let mut d = Deserializer::new(data.as_slice()).unwrap();
+    assert!(d.deserialize_in_place(&mut s).is_err());
+    assert_eq!(s, "");
+
+    // a truncated vec keeps the elements that were read
+    let mut v = vec![0_u16; 4];
+    let data = write(&vec![1_u16, 2, 3], 0);
+    let mut d = Deserializer::new(&data[..data.len() - 1]).unwrap();
+    assert!(d.deserialize_in_place(&mut v).is_err());
+    assert_eq!(v[..2], [1, 2]);
+}
@@ -0,0 +1,60 @@
+use std::{num::{NonZeroI64, NonZeroU32, NonZeroU8, NonZeroUsize, Saturating, Wrapping}, sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering}};
+
+use binverse::{error::BinverseError, serialize::{Deserialize, FixedSize, Serialize}, streams::{Deserializer, Serializer}, testing::assert_roundtrip};
+
+fn bytes<T: Serialize>(t: &T) -> Vec<u8> {
+    let mut s = Serializer::new_no_revision(Vec::new());
+    t.serialize(&mut s).unwrap();
+    s.finish().unwrap()
+}
+
+fn read<T: Deserialize>(data: &[u8]) -> Result<T, BinverseError> {
+    Deserializer::new_no_revision(data, 0).deserialize()
+}
+
+#[test]
+fn sizes() {
+    for x in [0, 1, 127, 128, usize::MAX] {
+        assert_roundtrip(&x);
+    }
+    for x in [0, -1, 1, -64, 64, isize::MIN, isize::MAX] {
+        assert_roundtrip(&x);
+    }
+    // varints with zigzag encoding for isize
+    assert_eq!(bytes(&300_usize), [0xac, 0x02]);
+    assert_eq!(bytes(&-1_isize), [0x01]);
+    assert_eq!(bytes(&1_isize), [0x02]);
+    assert!(Deserializer::new_no_revision([0x80, 0x00].as_slice(), 0).strict().deserialize::<usize>().is_err());
+}
+
+#[test]
+fn chars() {
+    for c in ['a', 'ä', '\0', '\u{10ffff}', '🦀'] {
+        assert_roundtrip(&c);
+    }
+    assert_eq!(char::SIZE, 4);
+    assert!(matches!(read::<char>(&0xd800_u32.to_le_bytes()), Err(BinverseError::InvalidChar(0xd800))));
+    assert!(matches!(read::<char>(&0x110000_u32.to_le_bytes()), Err(BinverseError::InvalidChar(0x110000))));
+}
+
+#[test]
+fn wrappers() {
+    assert_roundtrip(&NonZeroU8::new(3).unwrap());
+    assert_roundtrip(&NonZeroI64::new(-5).unwrap());
+    assert_roundtrip(&NonZeroUsize::new(1000).unwrap());
+    assert_eq!(bytes(&NonZeroU32::new(7).unwrap()), bytes(&7_u32));
+    assert!(matches!(read::<NonZeroU32>(&[0; 4]), Err(BinverseError::ZeroValue)));
+    assert!(matches!(read::<NonZeroUsize>(&[0]), Err(BinverseError::ZeroValue)));
+
+    assert_roundtrip(&Wrapping(u16::MAX));
+    assert_roundtrip(&Saturating(-3_i8));
+    assert_eq!(<Wrapping<u64>>::SIZE, 8);
+
+    let atomics = (AtomicBool::new(true), AtomicI32::new(-7), AtomicUsize::new(300));
+    let data = bytes(&atomics);
+    assert_eq!(data, bytes(&(true, -7_i32, 300_usize)));
+    let (a, b, c): (AtomicBool, AtomicI32, AtomicUsize) = read(&data).unwrap();
+    assert!(a.load(Ordering::SeqCst));
+    assert_eq!(b.load(Ordering::SeqCst), -7);
+    assert_eq!(c.load(Ordering::SeqCst), 300);
+}
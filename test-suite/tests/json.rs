@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+
+use binverse::serializable;
+use binverse_json::{binverse_to_json, json_to_binverse, parse_layout, Error};
+use serde_json::json;
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+struct Entry {
+    name: SizeBytes<1, String>,
+    size: u32,
+    old: Removed<2, i16>,
+    tags: BTreeMap<String, Option<u64>>,
+    pos: [f32; 2],
+    kind: Result<char, u8>,
+    big: Added<2, u128>
+}
+
+fn layout() -> serde_json::Value {
+    json!({"struct": [
+        {"name": "name", "type": {"string": 1}},
+        {"name": "size", "type": "u32"},
+        {"name": "old", "type": "i16", "removed": 2},
+        {"name": "tags", "type": {"map": ["string", {"option": "u64"}]}},
+        {"name": "pos", "type": {"array": "f32", "len": 2}},
+        {"name": "kind", "type": {"enum": [{"name": "Ok", "type": "char"}, {"name": "Err", "type": "u8"}]}},
+        {"name": "big", "type": "u128", "added": 2}
+    ]})
+}
+
+#[test]
+fn roundtrip() {
+    let entry = Entry {
+        name: "file".to_owned(),
+        size: 4096,
+        tags: [("a".to_owned(), Some(1)), ("b".to_owned(), None)].into_iter().collect(),
+        pos: [0.1, -2.0],
+        kind: Err(3),
+        big: u128::MAX
+    };
+    let ty = parse_layout(&layout()).unwrap();
+    let data = binverse::write(Vec::new(), entry, 2).unwrap();
+    let json = binverse_to_json(&data, &ty, None).unwrap();
+    assert_eq!(json, json!({
+        "name": "file",
+        "size": 4096,
+        "tags": {"a": 1, "b": null},
+        "pos": [0.1, -2.0],
+        "kind": {"Err": 3},
+        "big": u128::MAX.to_string()
+    }));
+    assert_eq!(json_to_binverse(&json, &ty, 2, true).unwrap(), data);
+}
+
+#[test]
+fn old_revision() {
+    let ty = parse_layout(&layout()).unwrap();
+    let json = json!({
+        "name": "x",
+        "size": 1,
+        "old": -3,
+        "tags": {},
+        "pos": [1.0, 2.0],
+        "kind": {"Ok": "ü"}
+    });
+    let data = json_to_binverse(&json, &ty, 1, false).unwrap();
+    assert_eq!(binverse_to_json(&data, &ty, Some(1)).unwrap(), json);
+    // the field was removed in revision 2 and `big` is missing
+    assert!(json_to_binverse(&json, &ty, 2, false).is_err());
+}
+
+fn invalid_path(json: serde_json::Value) -> String {
+    let ty = parse_layout(&layout()).unwrap();
+    match json_to_binverse(&json, &ty, 2, false) {
+        Err(Error::Invalid { path, .. }) => path,
+        other => panic!("expected an invalid value error, got {:?}", other)
+    }
+}
+
+#[test]
+fn error_paths() {
+    let valid = json!({"name": "", "size": 0, "tags": {}, "pos": [0, 0], "kind": {"Err": 3}, "big": 0});
+    let with = |key: &str, value: serde_json::Value| {
+        let mut json = valid.clone();
+        json[key] = value;
+        json
+    };
+    assert_eq!(invalid_path(with("name", json!("a".repeat(256)))), "$.name");
+    assert_eq!(invalid_path(with("size", json!(-1))), "$.size");
+    assert_eq!(invalid_path(with("tags", json!({"x": -1}))), "$.tags.x");
+    assert_eq!(invalid_path(with("pos", json!([0, 0, 0]))), "$.pos");
+    assert_eq!(invalid_path(with("kind", json!({"Ok": "ab"}))), "$.kind.Ok");
+    assert_eq!(invalid_path(with("old", json!(0))), "$.old");
+    assert_eq!(invalid_path(with("tags", json!({"a.b[0]": -1}))), r#"$.tags["a.b[0]"]"#);
+
+    // tuples don't accept additional elements
+    let ty = parse_layout(&json!({"tuple": ["u8", "u8"]})).unwrap();
+    assert!(json_to_binverse(&json!([1, 2]), &ty, 0, false).is_ok());
+    match json_to_binverse(&json!([1, 2, 3]), &ty, 0, false) {
+        Err(Error::Invalid { path, message }) => assert_eq!((path.as_str(), message.as_str()), ("$", "expected 2 elements, found 3")),
+        other => panic!("expected an invalid value error, got {:?}", other)
+    }
+
+    let err = parse_layout(&json!({"list": {"tuple": ["u8", "u9"]}})).unwrap_err();
+    assert_eq!(err.to_string(), "$.list.tuple[1]: unknown type 'u9'");
+}
@@ -0,0 +1,67 @@
+use binverse::{lazy::{serialize_indexed, LazyVec}, serializable, serialize::{Deserialize, FixedSize, Serialize}, streams::{Deserializer, Serializer}};
+
+#[test]
+fn fixed_elements() {
+    let points: Vec<(i32, [u16; 2])> = (0..100).map(|i| (-i, [i as u16, 2 * i as u16])).collect();
+    assert_eq!(<(i32, [u16; 2])>::SIZE, 8);
+    let mut s = Serializer::new_no_revision(Vec::new());
+    points.serialize(&mut s).unwrap();
+    true.serialize(&mut s).unwrap();
+    let data = s.finish().unwrap();
+
+    let view = LazyVec::<(i32, [u16; 2])>::fixed(&data, 0).unwrap();
+    assert_eq!(view.len(), 100);
+    assert_eq!(view.get(42).unwrap(), Some((-42, [42, 84])));
+    assert_eq!(view.get(100).unwrap(), None);
+    assert_eq!(view.iter().collect::<Result<Vec<_>, _>>().unwrap(), points);
+    assert_eq!(view.encoded_len(), data.len() - 1);
+
+    assert!(LazyVec::<(i32, [u16; 2])>::fixed(&data[..100], 0).is_err());
+}
+
+#[test]
+fn indexed_elements() {
+    #[serializable]
+    #[derive(Debug, PartialEq, Clone)]
+    struct Asset {
+        name: String,
+        data: Vec<u8>
+    }
+    let assets: Vec<Asset> = (0..20).map(|i| Asset {
+        name: format!("asset {}", i),
+        data: vec![i as u8; i * 10]
+    }).collect();
+
+    let mut s = Serializer::new(Vec::new(), 3).unwrap();
+    serialize_indexed(&mut s, &assets).unwrap();
+    5_u8.serialize(&mut s).unwrap();
+    let data = s.finish().unwrap();
+
+    let d = Deserializer::new(data.as_slice()).unwrap();
+    let revision = d.revision();
+    let rest = d.finish();
+    let view = LazyVec::<Asset>::indexed(rest, revision).unwrap();
+    assert_eq!(view.len(), 20);
+    assert_eq!(view.revision(), 3);
+    assert_eq!(view.get(13).unwrap().as_ref(), Some(&assets[13]));
+    assert_eq!(view.iter().collect::<Result<Vec<_>, _>>().unwrap(), assets);
+    let mut d = Deserializer::new_no_revision(&rest[view.encoded_len()..], revision);
+    assert_eq!(u8::deserialize(&mut d).unwrap(), 5);
+
+    let empty: [Asset; 0] = [];
+    let mut s = Serializer::new_no_revision(Vec::new());
+    serialize_indexed(&mut s, &empty).unwrap();
+    let data = s.finish().unwrap();
+    let view = LazyVec::<Asset>::indexed(&data, 0).unwrap();
+    assert!(view.is_empty());
+    assert_eq!(view.get(0).unwrap(), None);
+
+    // corrupted offsets are reported as errors
+    let mut s = Serializer::new_no_revision(Vec::new());
+    serialize_indexed(&mut s, &assets[..2]).unwrap();
+    let mut data = s.finish().unwrap();
+    data[1 + 8] = 200;
+    let view = LazyVec::<Asset>::indexed(&data, 0).unwrap();
+    assert!(view.get(0).is_err());
+    assert!(view.get(1).is_err());
+}
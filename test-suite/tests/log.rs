@@ -0,0 +1,146 @@
+use std::{fs::OpenOptions, ops::Bound, path::PathBuf};
+
+use binverse::{error::BinverseError, log::Log, serializable};
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("binverse-test-{}-{}.log", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+struct Event {
+    id: u64,
+    name: String
+}
+
+fn event(id: u64) -> Event {
+    Event { id, name: format!("event {}", id) }
+}
+
+#[test]
+fn append_and_read() {
+    let path = temp_path("append");
+    let mut log = Log::open(&path, 0).unwrap().with_index_interval(4);
+    assert!(log.is_empty());
+    for i in 0..10 {
+        assert_eq!(log.append(&event(i)).unwrap(), i as usize);
+    }
+    assert_eq!(log.get(7).unwrap(), Some(event(7)));
+    assert_eq!(log.get(10).unwrap(), None);
+    log.close().unwrap();
+
+    // reopened using the index, opening doesn't modify the file
+    let len = std::fs::metadata(&path).unwrap().len();
+    let mut log = Log::<Event>::open(&path, 0).unwrap();
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), len);
+    assert_eq!(log.len(), 10);
+    assert_eq!(log.get(3).unwrap(), Some(event(3)));
+    log.append(&event(10)).unwrap();
+    let events = log.range(8..).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(events, vec![event(8), event(9), event(10)]);
+    assert_eq!(log.range(2..=3).count(), 2);
+    assert_eq!(log.range(..=usize::MAX).count(), 11);
+    assert_eq!(log.range((Bound::Excluded(usize::MAX), Bound::Unbounded)).count(), 0);
+    drop(log);
+
+    // reopened without a footer
+    let log = Log::<Event>::open(&path, 0).unwrap();
+    let events = log.iter().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(events, (0..=10).map(event).collect::<Vec<_>>());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn truncated_tail() {
+    let path = temp_path("truncated");
+    let mut log = Log::open(&path, 0).unwrap().with_index_interval(2);
+    for i in 0..5 {
+        log.append(&event(i)).unwrap();
+    }
+    drop(log);
+
+    // simulate a crash while the last record was written
+    let file = OpenOptions::new().write(true).open(&path).unwrap();
+    let len = file.metadata().unwrap().len();
+    file.set_len(len - 3).unwrap();
+    drop(file);
+
+    let mut log = Log::<Event>::open(&path, 0).unwrap();
+    assert_eq!(log.len(), 4);
+    assert_eq!(log.get(3).unwrap(), Some(event(3)));
+    // the partial record is only removed by the next write
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), len - 3);
+    log.append(&event(4)).unwrap();
+    log.close().unwrap();
+
+    let log = Log::<Event>::open(&path, 0).unwrap();
+    let events = log.iter().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(events, (0..5).map(event).collect::<Vec<_>>());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn corrupted_record() {
+    let path = temp_path("corrupted");
+    let mut log = Log::open(&path, 0).unwrap();
+    for i in 0..5 {
+        log.append(&event(i)).unwrap();
+    }
+    drop(log);
+
+    // change a byte in the name of the second record
+    let mut data = std::fs::read(&path).unwrap();
+    let pos = data.windows(7).rposition(|w| w == b"event 1").unwrap();
+    data[pos] = b'E';
+    std::fs::write(&path, &data).unwrap();
+
+    // the records after it are still there, so this isn't a partial write
+    assert!(matches!(Log::<Event>::open(&path, 0), Err(BinverseError::InvalidData)));
+    assert_eq!(std::fs::read(&path).unwrap(), data);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn concurrent_reads() {
+    let path = temp_path("concurrent");
+    let mut log = Log::open(&path, 0).unwrap();
+    for i in 0..100 {
+        log.append(&event(i)).unwrap();
+    }
+    let log = &log;
+    std::thread::scope(|scope| {
+        for t in 0..4 {
+            scope.spawn(move || for i in (t..100).step_by(4) {
+                assert_eq!(log.get(i as usize).unwrap(), Some(event(i)));
+            });
+        }
+    });
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn revisions() {
+    let path = temp_path("revisions");
+    {
+        #[serializable]
+        struct Event {
+            id: u64
+        }
+        let mut log = Log::open(&path, 0).unwrap();
+        log.append(&Event { id: 1 }).unwrap();
+        log.close().unwrap();
+    }
+    #[serializable]
+    #[derive(Debug, PartialEq)]
+    struct Event {
+        id: u64,
+        tag: Added<1, String>
+    }
+    let mut log = Log::open(&path, 1).unwrap();
+    log.append(&Event { id: 2, tag: "new".to_owned() }).unwrap();
+    assert_eq!(log.get(0).unwrap(), Some(Event { id: 1, tag: String::new() }));
+    assert_eq!(log.get(1).unwrap(), Some(Event { id: 2, tag: "new".to_owned() }));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -1,6 +1,6 @@
 use std::fmt::Debug;
 
-use binverse::{serialize::Serialize, streams::{Deserializer, Serializer}};
+use binverse::{error::BinverseError, serialize::Serialize, streams::{Deserializer, Serializer}};
 use binverse_derive::serializable;
 
 #[test]
@@ -21,7 +21,7 @@ fn basic_struct() {
 
     let mut serializer = Serializer::new(Vec::new(), 0).unwrap();
     example.serialize(&mut serializer).unwrap();
-    let data = serializer.finish();
+    let data = serializer.finish().unwrap();
 
     assert_eq!(data.len(), 
         4 + // revision (version of the data) 
@@ -40,6 +40,7 @@ fn basic_struct() {
 }
 
 #[test]
+#[allow(dead_code)]
 fn simple_macro_attribs() {
     // struct with named fields
     #[serializable]
@@ -79,7 +80,7 @@ fn versioning() {
             b: "This is a string".to_owned(),
             c: 12345
         }.serialize(&mut s).unwrap();
-        s.finish()
+        s.finish().unwrap()
     };
 
     // In revision 1, field b was removed and field d was added.
@@ -110,7 +111,7 @@ fn versioning() {
             c: 56,
             d: 78
         }.serialize(&mut s).unwrap();
-        s.finish()
+        s.finish().unwrap()
     };
 
     // In revision 2 'b' was re-added and 'c' was removed again.
@@ -171,7 +172,7 @@ fn size_bytes() {
             c: str_c.to_owned(),
             d: str_d.to_owned()
         }.serialize(&mut s0).unwrap();
-        let data0 = s0.finish();
+        let data0 = s0.finish().unwrap();
         
         assert_eq!(data0.len(),
             4 + // revision
@@ -202,3 +203,64 @@ fn size_bytes() {
         });
     }
 }
+
+#[test]
+fn downgrade() {
+    #[serializable]
+    #[derive(PartialEq, Debug)]
+    struct Example {
+        a: u16,
+        b: Added<2, SizeBytes<1, String>>,
+        c: Added<1, u8>,
+        e: Removed<2, SizeBytes<1, String>>,
+        d: Added<3, u32>
+    }
+    let example = Example { a: 7, b: "new".to_owned(), c: 1, d: 9 };
+
+    // data for revision 1 readers only contains the fields they know, the
+    // removed field is written with its default value
+    let mut s = Serializer::new(Vec::new(), 1).unwrap();
+    assert_eq!(s.revision(), 1);
+    example.serialize(&mut s).unwrap();
+    let data = s.finish().unwrap();
+    assert_eq!(data, [1, 0, 0, 0, 7, 0, 1, 0]);
+
+    #[serializable]
+    #[derive(PartialEq, Debug)]
+    struct Old {
+        a: u16,
+        c: Added<1, u8>,
+        e: SizeBytes<1, String>
+    }
+    let mut d = Deserializer::new(data.as_slice()).unwrap();
+    assert_eq!(d.deserialize::<Old>().unwrap(), Old { a: 7, c: 1, e: String::new() });
+    assert!(d.finish().is_empty());
+
+    // reading it back as the current struct at the old revision skips the removed field
+    let mut d = Deserializer::new(data.as_slice()).unwrap();
+    assert_eq!(d.deserialize::<Example>().unwrap(), Example { b: String::new(), d: 0, ..example });
+    assert!(d.finish().is_empty());
+
+    let mut s = Serializer::new_no_revision(Vec::new()).for_revision(2);
+    example.serialize(&mut s).unwrap();
+    let data = s.finish().unwrap();
+    let mut d = Deserializer::new_no_revision(data.as_slice(), 2);
+    assert_eq!(d.deserialize::<Example>().unwrap(), Example { d: 0, ..example });
+
+    // removed fields without a default value can't be written for old revisions
+    #[serializable]
+    struct NoDefault(u8);
+    #[serializable]
+    struct WithRemoved {
+        a: u8,
+        b: Removed<1, NoDefault>
+    }
+    let mut s = Serializer::new(Vec::new(), 0).unwrap();
+    assert!(matches!(
+        WithRemoved { a: 1 }.serialize(&mut s),
+        Err(BinverseError::RemovedFieldWithoutDefault)
+    ));
+    let mut s = Serializer::new(Vec::new(), 1).unwrap();
+    WithRemoved { a: 1 }.serialize(&mut s).unwrap();
+    assert_eq!(s.finish().unwrap(), [1, 0, 0, 0, 1]);
+}
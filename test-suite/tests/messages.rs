@@ -0,0 +1,101 @@
+use binverse::{error::BinverseError, messages::{MessageReader, MessageWriter}, serializable};
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+struct Message {
+    id: u32,
+    text: String
+}
+
+fn message(id: u32, text: &str) -> Message {
+    Message { id, text: text.to_owned() }
+}
+
+#[test]
+fn message_stream() {
+    let mut w = MessageWriter::new(Vec::new(), 3).unwrap();
+    w.write(&message(1, "first")).unwrap();
+    w.write(&message(2, "")).unwrap();
+    w.write(&message(3, &"long".repeat(100))).unwrap();
+    let data = w.finish().unwrap();
+    // revision + (length + id + string length + string) for the first message
+    assert_eq!(&data[4..5], &[4 + 1 + 5]);
+
+    let r = MessageReader::<_, Message>::new(data.as_slice()).unwrap();
+    assert_eq!(r.revision(), 3);
+    let messages = r.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(messages, vec![message(1, "first"), message(2, ""), message(3, &"long".repeat(100))]);
+}
+
+#[test]
+fn corrupt_messages() {
+    let mut w = MessageWriter::new(Vec::new(), 0).unwrap();
+    // invalid utf8 inside of the first message
+    w.write(&(1_u32, vec![0xFF_u8; 5])).unwrap();
+    // the second message has trailing data that isn't read
+    w.write(&(2_u32, "second".to_owned(), 0_u8)).unwrap();
+    w.write(&message(3, "third")).unwrap();
+    let data = w.finish().unwrap();
+
+    let mut r = MessageReader::<_, Message>::new(data.as_slice()).unwrap();
+    assert!(matches!(r.next(), Some(Err(BinverseError::InvalidUTF8))));
+    assert!(matches!(r.next(), Some(Err(BinverseError::LengthMismatch { expected: 12, found: 11 }))));
+    assert_eq!(r.next().unwrap().unwrap(), message(3, "third"));
+    assert!(r.next().is_none());
+
+    // the stream ends in the middle of a message
+    let mut r = MessageReader::<_, Message>::new(&data[..data.len() - 1]).unwrap();
+    assert!(r.next().unwrap().is_err());
+    assert!(r.next().unwrap().is_err());
+    assert!(matches!(r.next(), Some(Err(BinverseError::IO(_)))));
+    assert!(r.next().is_none());
+
+    let mut r = MessageReader::<_, Message>::new(data.as_slice()).unwrap().with_max_len(9);
+    assert!(matches!(r.next(), Some(Err(BinverseError::MessageTooLarge { limit: 9, found: 10 }))));
+    assert!(matches!(r.next(), Some(Err(BinverseError::MessageTooLarge { limit: 9, found: 12 }))));
+    assert!(matches!(r.next(), Some(Err(BinverseError::MessageTooLarge { limit: 9, found: 10 }))));
+    assert!(r.next().is_none());
+}
+
+#[test]
+fn oversized_messages() {
+    let mut w = MessageWriter::new(Vec::new(), 0).unwrap();
+    w.write(&message(1, "short")).unwrap();
+    w.write(&message(2, &"x".repeat(1000))).unwrap();
+    w.write(&message(3, "after")).unwrap();
+    let data = w.finish().unwrap();
+
+    let mut r = MessageReader::<_, Message>::new(data.as_slice()).unwrap().with_max_len(100);
+    assert_eq!(r.next().unwrap().unwrap(), message(1, "short"));
+    assert!(matches!(r.next(), Some(Err(BinverseError::MessageTooLarge { limit: 100, found: 1006 }))));
+    assert_eq!(r.next().unwrap().unwrap(), message(3, "after"));
+    assert!(r.next().is_none());
+}
+
+#[test]
+fn resync_after_corrupt_frames() {
+    let mut w = MessageWriter::new(Vec::new(), 0).unwrap();
+    for (id, text) in [(1, "first"), (2, "second"), (3, "third"), (4, "fourth"), (5, "fifth")] {
+        w.write(&message(id, text)).unwrap();
+    }
+    let mut data = w.finish().unwrap();
+    // revision, then length + payload + checksum for every message
+    let second = 4 + (1 + 10 + 4);
+    let third = second + (1 + 11 + 4);
+    let fourth = third + (1 + 10 + 4);
+    // a damaged byte inside of the fourth message
+    data[fourth + 1 + 6] ^= 1;
+    // a damaged length of the third message
+    data[third] = 3;
+    // garbage in front of the second message that overflows a varint
+    data.splice(second..second, [0xFF; 11]);
+
+    let mut r = MessageReader::<_, Message>::new(data.as_slice()).unwrap();
+    assert_eq!(r.next().unwrap().unwrap(), message(1, "first"));
+    assert!(matches!(r.next(), Some(Err(BinverseError::VarIntOverflow))));
+    assert_eq!(r.next().unwrap().unwrap(), message(2, "second"));
+    // the damaged third and fourth message are reported as one error
+    assert!(matches!(r.next(), Some(Err(BinverseError::InvalidData))));
+    assert_eq!(r.next().unwrap().unwrap(), message(5, "fifth"));
+    assert!(r.next().is_none());
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use binverse::{error::BinverseResult, migrate::{Migrate, Migrations}, serializable, streams::Decoder};
+
+// revisions 0..=3: a list of items
+#[serializable]
+#[derive(Debug, PartialEq)]
+struct InventoryV1 {
+    owner: String,
+    items: Vec<(String, u32)>
+}
+
+// revisions 4..=7: the list became a map, the owner gained an id
+#[serializable]
+#[derive(Debug, PartialEq)]
+struct InventoryV2 {
+    owner: String,
+    owner_id: Added<6, u64>,
+    items: HashMap<String, u32>
+}
+impl From<InventoryV1> for InventoryV2 {
+    fn from(v1: InventoryV1) -> Self {
+        Self { owner: v1.owner, owner_id: 0, items: v1.items.into_iter().collect() }
+    }
+}
+
+// revision 8 and later: the owner was split into a separate struct
+#[serializable]
+#[derive(Debug, PartialEq)]
+struct Owner {
+    name: String,
+    id: u64
+}
+#[serializable(migrate)]
+#[derive(Debug, PartialEq)]
+struct Inventory {
+    owner: Owner,
+    items: HashMap<String, u32>
+}
+impl From<InventoryV2> for Inventory {
+    fn from(v2: InventoryV2) -> Self {
+        Self { owner: Owner { name: v2.owner, id: v2.owner_id }, items: v2.items }
+    }
+}
+impl Migrate for Inventory {
+    fn deserialize_migrated<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        Migrations::new()
+            .revisions::<InventoryV2>(4..=7)
+            .revisions::<InventoryV1>(0..=3)
+            .deserialize(d)
+    }
+}
+
+fn expected(id: u64) -> Inventory {
+    Inventory {
+        owner: Owner { name: "player".to_owned(), id },
+        items: HashMap::from([("sword".to_owned(), 1), ("arrow".to_owned(), 20)])
+    }
+}
+
+#[test]
+fn migrate_chain() {
+    let v1 = InventoryV1 { owner: "player".to_owned(), items: vec![("sword".to_owned(), 1), ("arrow".to_owned(), 20)] };
+    let data = binverse::write(Vec::new(), v1, 2).unwrap();
+    let (inventory, rest): (Inventory, _) = binverse::read(data.as_slice()).unwrap();
+    assert_eq!(inventory, expected(0));
+    assert!(rest.is_empty());
+
+    for (revision, id) in [(5, 0), (7, 12)] {
+        let v2 = InventoryV2 { owner: "player".to_owned(), owner_id: 12, items: expected(0).items };
+        let data = binverse::write(Vec::new(), v2, revision).unwrap();
+        let (inventory, rest): (Inventory, _) = binverse::read(data.as_slice()).unwrap();
+        assert_eq!(inventory, expected(id));
+        assert!(rest.is_empty());
+    }
+
+    let data = binverse::write(Vec::new(), expected(3), 8).unwrap();
+    let (inventory, _): (Inventory, _) = binverse::read(data.as_slice()).unwrap();
+    assert_eq!(inventory, expected(3));
+
+    let v1 = InventoryV1 { owner: "player".to_owned(), items: vec![("sword".to_owned(), 1), ("arrow".to_owned(), 20)] };
+    let data = binverse::write_no_revision(Vec::new(), v1).unwrap();
+    let (inventory, _): (Inventory, _) = binverse::read_no_revision(data.as_slice(), 3).unwrap();
+    assert_eq!(inventory, expected(0));
+}
+
+#[test]
+#[should_panic(expected = "from the newest to the oldest")]
+fn registration_order() {
+    let _ = Migrations::<Inventory>::new()
+        .revisions::<InventoryV2>(0..=3)
+        .revisions::<InventoryV1>(4..=7);
+}
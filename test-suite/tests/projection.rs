@@ -0,0 +1,78 @@
+use binverse::{error::BinverseError, serializable, streams::Deserializer};
+
+#[serializable(projection)]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Record {
+    pub id: u64,
+    pub name: SizeBytes<1, String>,
+    pub old: Removed<2, Vec<u32>>,
+    pub payload: Vec<String>,
+    pub score: Added<1, f32>,
+    pub tags: Added<2, Vec<u16>>
+}
+
+fn records() -> Vec<Record> {
+    (0..3).map(|i| Record {
+        id: i,
+        name: format!("record {}", i),
+        payload: vec!["x".repeat(i as usize * 100); 3],
+        score: i as f32 / 2.0,
+        tags: vec![i as u16; 2]
+    }).collect()
+}
+
+#[test]
+fn project_fields() {
+    let records = records();
+    let data = binverse::write(Vec::new(), records.clone(), 2).unwrap();
+    let mut d = Deserializer::new(data.as_slice()).unwrap();
+    let len: usize = d.deserialize::<binverse::varint::VarInt>().unwrap().0 as usize;
+    assert_eq!(len, records.len());
+    for record in &records {
+        let projection = Record::project(&mut d, &["id", "score"]).unwrap();
+        assert_eq!(projection.id, Some(record.id));
+        assert_eq!(projection.score, Some(record.score));
+        assert_eq!(projection.name, None);
+        assert_eq!(projection.payload, None);
+        assert_eq!(projection.tags, None);
+    }
+    assert!(d.finish().is_empty());
+}
+
+#[test]
+fn same_bytes_as_full_deserialize() {
+    // revision 0 data still contains `old` and doesn't contain `score` and `tags`
+    #[serializable]
+    struct V0 {
+        id: u64,
+        name: SizeBytes<1, String>,
+        old: Vec<u32>,
+        payload: Vec<String>
+    }
+    let v0 = V0 { id: 5, name: "five".to_owned(), old: vec![1, 2, 3], payload: vec!["a".to_owned()] };
+    let data = binverse::write(Vec::new(), v0, 0).unwrap();
+
+    let mut full = Deserializer::new(data.as_slice()).unwrap();
+    let record: Record = full.deserialize().unwrap();
+    for fields in [&[][..], &["name"], &["score", "tags"], &["id", "name", "payload", "score", "tags"]] {
+        let mut d = Deserializer::new(data.as_slice()).unwrap();
+        let projection = Record::project(&mut d, fields).unwrap();
+        assert_eq!(d.bytes_read(), full.bytes_read());
+        // fields that don't exist in the revision get their default value
+        assert_eq!(projection.score, fields.contains(&"score").then_some(record.score));
+        assert_eq!(projection.tags, fields.contains(&"tags").then(Vec::new));
+    }
+}
+
+#[test]
+fn unknown_field() {
+    #[serializable(projection)]
+    struct Pair(u8, SizeBytes<2, String>);
+
+    let data = binverse::write_no_revision(Vec::new(), Pair(1, "b".to_owned())).unwrap();
+    let mut d = Deserializer::new_no_revision(data.as_slice(), 0);
+    let projection = Pair::project(&mut d, &["1"]).unwrap();
+    assert_eq!((projection.0, projection.1.as_deref()), (None, Some("b")));
+    let result = Pair::project(&mut Deserializer::new_no_revision(data.as_slice(), 0), &["0", "missing"]);
+    assert!(matches!(result, Err(BinverseError::UnknownField { index: 1 })));
+}
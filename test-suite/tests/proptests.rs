@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use binverse::{serializable, serialize::SizeBytes, testing::{self, string_for, vec_for}, varint::VarInt};
+use proptest::prelude::*;
+
+#[serializable]
+#[derive(Debug, PartialEq, Clone)]
+struct Record {
+    id: VarInt,
+    name: SizeBytes<1, String>,
+    values: Vec<i32>,
+    flag: Option<bool>
+}
+
+fn record() -> impl Strategy<Value = Record> {
+    (any::<VarInt>(), string_for(SizeBytes::One, 255), any::<Vec<i32>>(), any::<Option<bool>>())
+        .prop_map(|(id, name, values, flag)| Record { id, name, values, flag })
+}
+
+proptest! {
+    #[test]
+    fn varints(x in any::<VarInt>()) {
+        testing::assert_roundtrip(&x);
+    }
+
+    #[test]
+    fn sized_strings(sb in any::<SizeBytes>(), s in any::<String>()) {
+        prop_assume!(s.len() as u64 <= sb.maximum());
+        testing::assert_roundtrip_sized(&s, sb);
+    }
+
+    #[test]
+    fn sized_vecs(v in vec_for(any::<u16>(), SizeBytes::One, 1000)) {
+        prop_assert!(v.len() <= 255);
+        testing::assert_roundtrip_sized(&v, SizeBytes::One);
+    }
+
+    #[test]
+    fn records(r in record(), map in any::<HashMap<u8, String>>()) {
+        testing::assert_roundtrip(&r);
+        testing::assert_roundtrip(&map);
+    }
+}
+
+#[test]
+fn revisions() {
+    #[serializable]
+    struct Old {
+        a: u32,
+        b: String
+    }
+    #[serializable]
+    #[derive(Debug, PartialEq)]
+    struct New {
+        a: u32,
+        b: Removed<1, String>,
+        c: Added<1, u8>
+    }
+    testing::assert_cross_revision(&Old { a: 5, b: "removed".to_owned() }, 0, &New { a: 5, c: 0 });
+    testing::assert_roundtrip_at(&New { a: 1, c: 2 }, 1);
+    testing::assert_roundtrip(&New { a: 1, c: 2 });
+    assert!(testing::cross_revision::<Old, New>(&Old { a: 5, b: "x".to_owned() }, 1).is_err());
+}
@@ -6,7 +6,7 @@ use binverse_derive::serializable;
 fn reserialize_test<T : Serialize + Deserialize + PartialEq + Debug>(val: T) {
     let mut s = Serializer::new(Vec::new(), 0).unwrap();
     val.serialize(&mut s).unwrap();
-    let buf = s.finish();
+    let buf = s.finish().unwrap();
     let mut d = Deserializer::new(Cursor::new(buf)).unwrap();
     let new_val: T = d.deserialize().unwrap();
     assert_eq!(val, new_val, "{}", std::any::type_name::<T>());
@@ -15,10 +15,10 @@ fn reserialize_test<T : Serialize + Deserialize + PartialEq + Debug>(val: T) {
     assert_eq!(finished.into_inner().len() as u64 - pos, 0, "leftover bytes after deserialing");
 }
 
-fn reserialize_sized_test<'a, T : SizedSerialize + SizedDeserialize + PartialEq + Debug>(val: T, sb: SizeBytes) {
+fn reserialize_sized_test<T : SizedSerialize + SizedDeserialize + PartialEq + Debug>(val: T, sb: SizeBytes) {
     let mut s = Serializer::new(Vec::new(), 0).unwrap();
     s.serialize_sized(sb, &val).unwrap();
-    let buf = s.finish();
+    let buf = s.finish().unwrap();
     let mut d = Deserializer::new(Cursor::new(buf)).unwrap();
     let new_val: T = d.deserialize_sized(sb).unwrap();
     assert_eq!(val, new_val, "{}", std::any::type_name::<T>());
@@ -27,7 +27,7 @@ fn reserialize_sized_test<'a, T : SizedSerialize + SizedDeserialize + PartialEq
     assert_eq!(finished.into_inner().len() as u64 - pos, 0, "leftover bytes after deserialing");
 }
 
-fn test_all<'a, T>(vals: &[T]) 
+fn test_all<T>(vals: &[T]) 
 where T : Serialize + Deserialize + PartialEq + Debug + Clone {
     for val in vals {
         reserialize_test(val.clone());
@@ -115,6 +115,17 @@ fn primitive_serialization() {
     reserialize_sized_test(map, SizeBytes::One);
 }
 
+#[test]
+fn large_tuples() {
+    type Tuple = (u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u16, u8, u8, u8, u32);
+    let tuple: Tuple = (0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+    let data = binverse::write_no_revision(Vec::new(), tuple).unwrap();
+    assert_eq!(data, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 0, 13, 14, 15, 16, 0, 0, 0]);
+    let (read, rest): (Tuple, &[u8]) = binverse::read_no_revision(data.as_slice(), 0).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!((read.11, read.12, read.13, read.16), (11, 12, 13, 16));
+}
+
 #[test]
 fn array_drop() {
     let mut d = Deserializer::new_no_revision([1_u8, 0, 0, 1, 1].as_slice(), 0);
@@ -175,7 +186,7 @@ fn structs() {
         },
         Example1 {
             position: Vec3 { x: 3543.4, y: 3.0, z: std::f32::consts::PI },
-            name: String::from(format!("An entity with a very long name: {}", "VeryLongName".repeat(10000))),
+            name: format!("An entity with a very long name: {}", "VeryLongName".repeat(10000)),
             alive: false
         },
     ]);
@@ -0,0 +1,133 @@
+use std::{collections::BTreeMap, io::{self, Cursor, Read, Seek, SeekFrom}};
+
+use binverse::{serializable, serialize::{Deserialize, Serialize, SizeBytes}, streams::{Deserializer, Serializer}, varint::VarInt};
+
+/// Counts the bytes read from a cursor.
+struct CountingCursor {
+    inner: Cursor<Vec<u8>>,
+    read: usize
+}
+impl Read for CountingCursor {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n;
+        Ok(n)
+    }
+}
+impl Seek for CountingCursor {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+fn check_skip<T: Serialize + Deserialize>(value: T) {
+    let mut s = Serializer::new_no_revision(Vec::new());
+    value.serialize(&mut s).unwrap();
+    255_u8.serialize(&mut s).unwrap();
+    let data = s.finish().unwrap();
+    let mut d = Deserializer::new_no_revision(data.as_slice(), 0);
+    d.skip_value::<T>().unwrap();
+    assert_eq!(d.bytes_read() as usize, data.len() - 1);
+    assert_eq!(d.deserialize::<u8>().unwrap(), 255);
+}
+
+#[test]
+fn skip_values() {
+    check_skip(true);
+    check_skip(-5_i64);
+    check_skip(1.5_f32);
+    check_skip('ß');
+    check_skip(usize::MAX);
+    check_skip(VarInt(300));
+    check_skip([[1_u16; 3]; 4]);
+    check_skip((Some(3_u8), None::<u32>, "tuple".to_owned()));
+    check_skip(vec![vec![1_u32, 2], vec![]]);
+    check_skip(BTreeMap::from([("a".to_owned(), vec![1.0_f64]), ("b".to_owned(), vec![])]));
+
+    let mut s = Serializer::new_no_revision(Vec::new());
+    s.serialize_sized(SizeBytes::Two, &"sized".to_owned()).unwrap();
+    let data = s.finish().unwrap();
+    let mut d = Deserializer::new_no_revision(data.as_slice(), 0);
+    d.skip_sized::<String>(SizeBytes::Two).unwrap();
+    assert!(d.finish().is_empty());
+}
+
+#[test]
+fn seekable() {
+    let big = vec![7_u64; 100_000];
+    let mut s = Serializer::new_no_revision(Vec::new());
+    big.serialize(&mut s).unwrap();
+    "after".serialize(&mut s).unwrap();
+    let data = s.finish().unwrap();
+    let len = data.len();
+
+    let r = CountingCursor { inner: Cursor::new(data), read: 0 };
+    let mut d = Deserializer::new_no_revision(r, 0).seekable().buffered(64);
+    d.skip_value::<Vec<u64>>().unwrap();
+    assert_eq!(d.deserialize::<String>().unwrap(), "after");
+    assert_eq!(d.bytes_read() as usize, len);
+    assert!(d.finish().read < 200);
+
+    // skipping past the end is only detected by the next read
+    let r = Cursor::new(vec![10]);
+    let mut d = Deserializer::new_no_revision(r, 0).seekable();
+    d.skip_value::<String>().unwrap();
+    assert!(d.deserialize::<u8>().is_err());
+}
+
+#[test]
+fn removed_fields() {
+    #[serializable]
+    struct V0 {
+        a: u8,
+        name: SizeBytes<2, String>,
+        samples: Vec<u32>,
+        b: u8
+    }
+    #[serializable]
+    #[derive(Debug, PartialEq)]
+    struct V1 {
+        a: u8,
+        name: Removed<1, SizeBytes<2, String>>,
+        samples: Removed<1, Vec<u32>>,
+        b: u8
+    }
+
+    let v0 = V0 { a: 1, name: "x".repeat(1000), samples: vec![3; 1000], b: 2 };
+    let data = binverse::write(Vec::new(), v0, 0).unwrap();
+    let r = CountingCursor { inner: Cursor::new(data), read: 0 };
+    let mut d = Deserializer::new(r).unwrap().seekable();
+    assert_eq!(d.deserialize::<V1>().unwrap(), V1 { a: 1, b: 2 });
+    // only the revision, the fields and the lengths are read
+    assert_eq!(d.finish().read, 4 + 1 + 2 + 2 + 1);
+}
+
+#[test]
+fn derived_structs() {
+    #[serializable]
+    struct Inner {
+        name: String,
+        samples: Vec<u32>
+    }
+    #[serializable]
+    struct V0 {
+        a: u8,
+        inner: Inner,
+        b: u8
+    }
+    #[serializable]
+    #[derive(Debug, PartialEq)]
+    struct V1 {
+        a: u8,
+        inner: Removed<1, Inner>,
+        b: u8
+    }
+
+    let v0 = V0 { a: 1, inner: Inner { name: "x".repeat(1000), samples: vec![3; 1000] }, b: 2 };
+    let data = binverse::write(Vec::new(), v0, 0).unwrap();
+    let r = CountingCursor { inner: Cursor::new(data), read: 0 };
+    let mut d = Deserializer::new(r).unwrap().seekable();
+    assert_eq!(d.deserialize::<V1>().unwrap(), V1 { a: 1, b: 2 });
+    // the fields of the inner struct are skipped without reading them
+    assert_eq!(d.finish().read, 4 + 1 + 2 + 2 + 1);
+}
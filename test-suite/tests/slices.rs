@@ -0,0 +1,44 @@
+use binverse::{error::BinverseError, serializable, serialize::Serialize, streams::Serializer};
+
+#[serializable]
+#[derive(Debug, PartialEq, Clone)]
+struct Packet {
+    id: u16,
+    payload: SizeBytes<1, Vec<u8>>
+}
+
+#[test]
+fn fixed_buffers() {
+    let packet = Packet { id: 7, payload: vec![1, 2, 3, 4, 5] };
+    let size = binverse::serialized_size(&packet, 1).unwrap();
+    assert_eq!(size, 4 + 2 + 1 + 5);
+    assert_eq!(binverse::serialized_size_no_revision(&packet).unwrap(), size - 4);
+
+    let mut buf = [0_u8; 256];
+    let len = binverse::write_slice(&mut buf, packet.clone(), 1).unwrap();
+    assert_eq!(len as u64, size);
+    let (read, _): (Packet, _) = binverse::read(&buf[..len]).unwrap();
+    assert_eq!(read, packet);
+
+    let len = binverse::write_slice_no_revision(&mut buf, packet.clone()).unwrap();
+    assert_eq!(binverse::read_no_revision::<_, Packet>(&buf[..len], 1).unwrap().0, packet);
+
+    // exactly large enough
+    let mut exact = vec![0; size as usize];
+    assert_eq!(binverse::write_slice(&mut exact, packet.clone(), 1).unwrap() as u64, size);
+
+    let mut small = [0_u8; 8];
+    match binverse::write_slice(&mut small, packet, 1) {
+        Err(BinverseError::BufferFull { needed: 9 }) => (),
+        other => panic!("Expected full buffer, got {:?}", other)
+    }
+
+    let mut buf = [0_u8; 16];
+    let mut s = Serializer::new_slice(&mut buf, 0).unwrap();
+    1_u32.serialize(&mut s).unwrap();
+    assert_eq!(s.bytes_written(), 8);
+    let w = s.finish().unwrap();
+    assert_eq!(w.len(), 8);
+    assert_eq!(w.capacity(), 16);
+    assert_eq!(w.written(), &[0, 0, 0, 0, 1, 0, 0, 0]);
+}
@@ -0,0 +1,63 @@
+use std::{net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6}, path::PathBuf, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use binverse::{error::BinverseError, serialize::{Deserialize, Serialize}, streams::{Deserializer, Serializer}, testing::assert_roundtrip};
+
+fn bytes<T: Serialize>(t: &T) -> Vec<u8> {
+    let mut s = Serializer::new_no_revision(Vec::new());
+    t.serialize(&mut s).unwrap();
+    s.finish().unwrap()
+}
+
+fn read<T: Deserialize>(data: &[u8]) -> Result<T, BinverseError> {
+    Deserializer::new_no_revision(data, 0).deserialize()
+}
+
+#[test]
+fn times() {
+    assert_roundtrip(&Duration::ZERO);
+    assert_roundtrip(&Duration::new(u64::MAX, 999_999_999));
+    let invalid = bytes(&(1_u64, 1_000_000_000_u32));
+    assert!(matches!(read::<Duration>(&invalid), Err(BinverseError::InvalidData)));
+
+    assert_roundtrip(&SystemTime::now());
+    assert_roundtrip(&UNIX_EPOCH);
+    let before = UNIX_EPOCH - Duration::from_millis(250);
+    assert_roundtrip(&before);
+    assert_eq!(bytes(&before), bytes(&(-1_i64, 750_000_000_u32)));
+    assert_eq!(bytes(&(UNIX_EPOCH - Duration::from_secs(2))), bytes(&(-2_i64, 0_u32)));
+}
+
+#[test]
+fn addresses() {
+    assert_roundtrip(&Ipv4Addr::new(192, 168, 0, 1));
+    assert_roundtrip(&IpAddr::V6(Ipv6Addr::LOCALHOST));
+    assert_eq!(bytes(&IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))), [0, 10, 0, 0, 1]);
+    assert!(matches!(read::<IpAddr>(&[2, 10, 0, 0, 1]), Err(BinverseError::InvalidData)));
+
+    let v4: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    assert_roundtrip(&v4);
+    assert_eq!(bytes(&v4).len(), 7);
+    assert_roundtrip(&SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, 443, 7, 3)));
+    assert!(read::<SocketAddr>(&[9]).is_err());
+}
+
+#[test]
+fn paths_ranges_results() {
+    assert_roundtrip(&PathBuf::from("/var/log/binverse.log"));
+    assert_eq!(bytes(&PathBuf::from("a/b")), bytes(&"a/b"));
+    #[cfg(unix)]
+    {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+        let invalid = PathBuf::from(OsStr::from_bytes(&[0xff, 0xfe]));
+        let mut s = Serializer::new_no_revision(Vec::new());
+        assert!(matches!(invalid.serialize(&mut s), Err(BinverseError::InvalidUTF8)));
+    }
+
+    assert_roundtrip(&(5_u64..100));
+    assert_roundtrip(&(-3_i8..=3));
+    assert_eq!(bytes(&(1_u16..2)), [1, 0, 2, 0]);
+
+    assert_roundtrip(&Ok::<u32, String>(5));
+    assert_roundtrip(&Err::<u32, String>("failed".to_owned()));
+    assert!(matches!(read::<Result<u8, u8>>(&[2, 0]), Err(BinverseError::InvalidData)));
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use binverse::{error::BinverseError, serializable, serialize::{Serialize, SizeBytes, SizedSerialize}, streams::{Deserializer, Serializer}, varint::VarInt};
+use proptest::prelude::*;
+
+#[serializable]
+#[derive(Debug)]
+struct Record {
+    id: VarInt,
+    name: SizeBytes<1, String>,
+    values: Vec<Option<f64>>,
+    tags: HashMap<u16, String>,
+    fixed: [String; 2]
+}
+
+#[test]
+fn huge_lengths() {
+    // A length prefix close to u64::MAX must fail with an error without
+    // allocating memory for the whole length.
+    let mut data = Vec::new();
+    VarInt(u64::MAX).serialize(&mut Serializer::new_no_revision(&mut data)).unwrap();
+    data.extend_from_slice(b"abc");
+    assert!(Deserializer::new_no_revision(data.as_slice(), 0).deserialize::<String>().is_err());
+    assert!(Deserializer::new_no_revision(data.as_slice(), 0).deserialize::<Vec<u64>>().is_err());
+    assert!(Deserializer::new_no_revision(data.as_slice(), 0).deserialize::<HashMap<u8, u8>>().is_err());
+}
+
+#[test]
+fn invalid_sizes() {
+    let mut s = Serializer::new_no_revision(Vec::new());
+    // the size is within the string, but not at a character boundary
+    assert!(matches!("ä".serialize_sized(&mut s, 1), Err(BinverseError::InvalidUTF8)));
+    assert!(matches!("abc".serialize_sized(&mut s, 4), Err(BinverseError::InvalidUTF8)));
+    assert!(matches!([1_u8, 2].as_slice().serialize_sized(&mut s, 3), Err(BinverseError::InvalidData)));
+    assert!(s.finish().unwrap().is_empty());
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_bytes(data in any::<Vec<u8>>(), strict in any::<bool>()) {
+        let mut d = Deserializer::new_no_revision(data.as_slice(), 0);
+        if strict {
+            d = d.strict();
+        }
+        let _ = d.deserialize::<Record>();
+        let _ = Deserializer::new_no_revision(data.as_slice(), 0).deserialize_sized::<String>(SizeBytes::Eight);
+    }
+}
@@ -0,0 +1,133 @@
+use std::{fs, path::PathBuf};
+
+use binverse::{error::{BinverseError, BinverseResult}, migrate::{Migrate, Migrations}, serializable, streams::Decoder, upgrade::{Upgrade, Upgrader}};
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+struct SaveV1 {
+    name: String,
+    level: u8
+}
+
+#[serializable(migrate)]
+#[derive(Debug, PartialEq)]
+struct Save {
+    name: String,
+    level: u32,
+    gold: Added<3, u64>
+}
+impl From<SaveV1> for Save {
+    fn from(v1: SaveV1) -> Self {
+        Self { name: v1.name, level: v1.level as u32, gold: 0 }
+    }
+}
+impl Migrate for Save {
+    fn deserialize_migrated<D: Decoder>(d: &mut D) -> BinverseResult<Self> {
+        Migrations::new().revisions::<SaveV1>(0..=1).deserialize(d)
+    }
+}
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("binverse-upgrade-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_file<T: binverse::serialize::Serialize>(path: &PathBuf, object: T, revision: u32) {
+    fs::write(path, binverse::write(Vec::new(), object, revision).unwrap()).unwrap();
+}
+
+#[test]
+fn upgrade_file() {
+    let dir = test_dir("file");
+    let path = dir.join("save.dat");
+    write_file(&path, SaveV1 { name: "hero".to_owned(), level: 7 }, 1);
+    let old = fs::read(&path).unwrap();
+
+    let upgrader = Upgrader::<Save>::new(4).backup("bak");
+    assert_eq!(upgrader.upgrade_file(&path).unwrap(), Upgrade::Upgraded { from: 1 });
+    assert_eq!(fs::read(dir.join("save.dat.bak")).unwrap(), old);
+    let (save, _): (Save, _) = binverse::read(fs::read(&path).unwrap().as_slice()).unwrap();
+    assert_eq!(save, Save { name: "hero".to_owned(), level: 7, gold: 0 });
+    assert_eq!(fs::read(&path).unwrap()[..4], 4u32.to_le_bytes());
+
+    // already upgraded files and files from newer revisions are left alone
+    let upgraded = fs::read(&path).unwrap();
+    assert_eq!(upgrader.upgrade_file(&path).unwrap(), Upgrade::Current);
+    assert_eq!(Upgrader::<Save>::new(3).upgrade_file(&path).unwrap(), Upgrade::Newer { revision: 4 });
+    assert_eq!(fs::read(&path).unwrap(), upgraded);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn invalid_files_are_kept() {
+    let dir = test_dir("invalid");
+    let path = dir.join("save.dat");
+    let mut data = binverse::write(Vec::new(), Save { name: "a".to_owned(), level: 1, gold: 5 }, 3).unwrap();
+    data.push(0);
+    fs::write(&path, &data).unwrap();
+
+    let upgrader = Upgrader::<Save>::new(4);
+    assert!(matches!(upgrader.upgrade_file(&path), Err(BinverseError::LengthMismatch { .. })));
+    fs::write(&path, &data[..data.len() - 3]).unwrap();
+    assert!(matches!(upgrader.upgrade_file(&path), Err(BinverseError::IO(_))));
+    assert_eq!(fs::read(&path).unwrap(), &data[..data.len() - 3]);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn upgrade_dir() {
+    let dir = test_dir("dir");
+    fs::create_dir(dir.join("nested")).unwrap();
+    write_file(&dir.join("a.sav"), SaveV1 { name: "a".to_owned(), level: 1 }, 0);
+    write_file(&dir.join("nested/b.sav"), Save { name: "b".to_owned(), level: 2, gold: 3 }, 2);
+    write_file(&dir.join("nested/c.sav"), Save { name: "c".to_owned(), level: 3, gold: 4 }, 4);
+    fs::write(dir.join("nested/corrupt.sav"), [1, 0, 0, 0, 200]).unwrap();
+    fs::write(dir.join("notes.txt"), "not a save").unwrap();
+
+    let results = Upgrader::<Save>::new(4).extension("sav").backup("bak").upgrade_dir(&dir).unwrap();
+    let results: Vec<_> = results.into_iter()
+        .map(|r| (r.path.strip_prefix(&dir).unwrap().to_owned(), r.result.ok()))
+        .collect();
+    assert_eq!(results, [
+        (PathBuf::from("a.sav"), Some(Upgrade::Upgraded { from: 0 })),
+        (PathBuf::from("nested/b.sav"), Some(Upgrade::Upgraded { from: 2 })),
+        (PathBuf::from("nested/c.sav"), Some(Upgrade::Current)),
+        (PathBuf::from("nested/corrupt.sav"), None)
+    ]);
+
+    // backups are skipped, without an extension filter all other files are checked
+    let results = Upgrader::<Save>::new(4).backup("bak").upgrade_dir(&dir).unwrap();
+    let failed: Vec<_> = results.iter().filter(|r| r.result.is_err()).map(|r| r.path.clone()).collect();
+    assert_eq!(failed, [dir.join("nested/corrupt.sav")]);
+    assert_eq!(results.len(), 5);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn concurrent_upgrades() {
+    let dir = test_dir("concurrent");
+    let path = dir.join("save.dat");
+    // a temporary file left by an older upgrader isn't touched
+    fs::write(dir.join("save.dat.upgrade-tmp"), "stale").unwrap();
+
+    let upgrader = Upgrader::<Save>::new(4).backup("bak");
+    for _ in 0..20 {
+        write_file(&path, SaveV1 { name: "hero".to_owned(), level: 7 }, 1);
+        let original = fs::read(&path).unwrap();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| assert!(upgrader.upgrade_file(&path).is_ok()));
+            }
+        });
+        let (save, _): (Save, _) = binverse::read(fs::read(&path).unwrap().as_slice()).unwrap();
+        assert_eq!(save, Save { name: "hero".to_owned(), level: 7, gold: 0 });
+        // the backup always contains the original data
+        assert_eq!(fs::read(dir.join("save.dat.bak")).unwrap(), original);
+        assert_eq!(fs::read(dir.join("save.dat.upgrade-tmp")).unwrap(), b"stale");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use binverse::{error::BinverseError, serializable, serialize::{Serialize, SizeBytes}, streams::{Deserializer, Serializer}, value::{Field, Type, Value, Variant}, varint::VarInt};
+use proptest::prelude::*;
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+struct Record {
+    id: VarInt,
+    offset: isize,
+    name: SizeBytes<1, String>,
+    old: Removed<2, u16>,
+    data: Vec<u8>,
+    scores: BTreeMap<String, [f32; 2]>,
+    parent: Option<u64>,
+    pair: (char, i8),
+    result: Result<u32, String>,
+    extra: Added<3, SizeBytes<2, Vec<i64>>>
+}
+
+fn record_type() -> Type {
+    Type::Struct(vec![
+        Field::new("id", Type::VarInt),
+        Field::new("offset", Type::Isize),
+        Field::new("name", Type::String(SizeBytes::One)),
+        Field::new("old", Type::U16).removed(2),
+        Field::new("data", Type::Bytes(SizeBytes::Var)),
+        Field::new("scores", Type::Map(Box::new(Type::String(SizeBytes::Var)), Box::new(Type::Array(Box::new(Type::F32), 2)), SizeBytes::Var)),
+        Field::new("parent", Type::Option(Box::new(Type::U64))),
+        Field::new("pair", Type::Tuple(vec![Type::Char, Type::I8])),
+        Field::new("result", Type::Enum(vec![Variant::new("Ok", Type::U32), Variant::new("Err", Type::String(SizeBytes::Var))])),
+        Field::new("extra", Type::List(Box::new(Type::I64), SizeBytes::Two)).added(3),
+    ])
+}
+
+fn record() -> Record {
+    Record {
+        id: VarInt(300),
+        offset: -2,
+        name: "rec".to_owned(),
+        data: vec![1, 2, 3],
+        scores: [("a".to_owned(), [1.5, f32::from_bits(0x7fa0_0001)])].into_iter().collect(),
+        parent: Some(7),
+        pair: ('ä', -1),
+        result: Err("failed".to_owned()),
+        extra: vec![-5, 5]
+    }
+}
+
+fn reencode(data: &[u8], ty: &Type, revision: u32) -> (Value, Vec<u8>) {
+    let mut d = Deserializer::new_no_revision(data, revision);
+    let value = Value::decode(&mut d, ty).unwrap();
+    assert!(d.finish().is_empty());
+    let mut s = Serializer::new_no_revision(Vec::new());
+    value.encode(&mut s, ty).unwrap();
+    (value, s.finish().unwrap())
+}
+
+#[test]
+fn decode_struct() {
+    let mut s = Serializer::new_no_revision(Vec::new());
+    record().serialize(&mut s).unwrap();
+    let data = s.finish().unwrap();
+
+    let (value, encoded) = reencode(&data, &record_type(), 3);
+    assert_eq!(encoded, data);
+    assert_eq!(value.field("id"), Some(&Value::UInt(300)));
+    assert_eq!(value.field("offset"), Some(&Value::Int(-2)));
+    assert_eq!(value.field("old"), None);
+    assert_eq!(value.field("pair"), Some(&Value::List(vec![Value::Char('ä'), Value::Int(-1)])));
+    assert_eq!(value.field("result"), Some(&Value::Enum("Err".to_owned(), Box::new(Value::String("failed".to_owned())))));
+    assert_eq!(value.field("extra"), Some(&Value::List(vec![Value::Int(-5), Value::Int(5)])));
+}
+
+#[test]
+fn old_revisions() {
+    // revision 1 has the removed field but not the added one
+    let mut s = Serializer::new_no_revision(Vec::new());
+    VarInt(1).serialize(&mut s).unwrap();
+    0_isize.serialize(&mut s).unwrap();
+    s.serialize_sized(SizeBytes::One, &"x").unwrap();
+    9_u16.serialize(&mut s).unwrap();
+    Vec::<u8>::new().serialize(&mut s).unwrap();
+    BTreeMap::<String, [f32; 2]>::new().serialize(&mut s).unwrap();
+    None::<u64>.serialize(&mut s).unwrap();
+    ('a', 0_i8).serialize(&mut s).unwrap();
+    Ok::<u32, String>(1).serialize(&mut s).unwrap();
+    let data = s.finish().unwrap();
+
+    let (value, encoded) = reencode(&data, &record_type(), 1);
+    assert_eq!(encoded, data);
+    assert_eq!(value.field("old"), Some(&Value::UInt(9)));
+    assert_eq!(value.field("extra"), None);
+}
+
+#[test]
+fn mismatches() {
+    let mut s = Serializer::new_no_revision(Vec::new());
+    assert!(matches!(Value::UInt(256).encode(&mut s, &Type::U8), Err(BinverseError::IntegerOverflow)));
+    assert!(matches!(Value::Int(1).encode(&mut s, &Type::U8), Err(BinverseError::InvalidData)));
+    assert!(matches!(Value::List(vec![]).encode(&mut s, &Type::Array(Box::new(Type::U8), 1)), Err(BinverseError::InvalidData)));
+    let ty = Type::Struct(vec![Field::new("a", Type::U8), Field::new("b", Type::U8)]);
+    let swapped = Value::Struct(vec![("b".to_owned(), Value::UInt(1)), ("a".to_owned(), Value::UInt(2))]);
+    assert!(matches!(swapped.encode(&mut s, &ty), Err(BinverseError::InvalidData)));
+    let enum_ty = Type::Enum(vec![Variant::new("A", Type::Tuple(vec![]))]);
+    assert!(Value::decode(&mut Deserializer::new_no_revision([1_u8].as_slice(), 0), &enum_ty).is_err());
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_bytes(data in any::<Vec<u8>>(), revision in 0..4_u32) {
+        let mut d = Deserializer::new_no_revision(data.as_slice(), revision);
+        if let Ok(value) = Value::decode(&mut d, &record_type()) {
+            let read = d.bytes_read() as usize;
+            let mut s = Serializer::new_no_revision(Vec::new());
+            value.encode(&mut s, &record_type()).unwrap();
+            prop_assert_eq!(s.finish().unwrap(), &data[..read]);
+        }
+    }
+}